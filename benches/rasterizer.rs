@@ -0,0 +1,90 @@
+//! Compares `fill_polygon_canvas`, which starts a fresh `Rasterizer` on every call,
+//! against one reused `Rasterizer`.
+//!
+//! Run with `cargo bench --bench rasterizer`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use palette::rgb::LinSrgba;
+use polygon_canvas::{
+    algorithms::{
+        fill_polygon::{fill_polygon_canvas, FillRule},
+        rasterizer::Rasterizer,
+    },
+    canvas::{BlendMode, Canvas},
+    geometry::{Point, Polygon},
+    utils::Rng,
+};
+
+const SIZE: usize = 256;
+const POLYGONS: usize = 200;
+const ROUNDS: usize = 20;
+
+fn random_polygons(rng: &mut Rng, vertices: usize) -> Vec<Polygon<f64>> {
+    (0..POLYGONS)
+        .map(|_| {
+            let mut poly = Polygon::new();
+            for _ in 0..vertices {
+                poly.add_point(Point::new(
+                    rng.range_f64(0.0, SIZE as f64),
+                    rng.range_f64(0.0, SIZE as f64),
+                ));
+            }
+            poly
+        })
+        .collect()
+}
+
+fn time(mut f: impl FnMut()) -> Duration {
+    // One untimed round to warm caches and grow buffers
+    f();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    start.elapsed() / (ROUNDS * POLYGONS) as u32
+}
+
+fn main() {
+    let mut rng = Rng::new(1);
+    let color = LinSrgba::new(0.3, 0.5, 0.7, 0.5);
+    let mut canvas = Canvas::from_wh(SIZE, SIZE);
+    let mut rasterizer = Rasterizer::new();
+
+    println!(
+        "{:<12} {:>16} {:>16}",
+        "vertices", "fill_polygon", "Rasterizer"
+    );
+    for vertices in [3, 4, 8, 32, 128] {
+        let polygons = random_polygons(&mut rng, vertices);
+        let function = time(|| {
+            for poly in &polygons {
+                fill_polygon_canvas(
+                    &mut canvas,
+                    poly,
+                    color,
+                    FillRule::NonZero,
+                    BlendMode::Normal,
+                )
+                .unwrap();
+            }
+            black_box(&canvas);
+        });
+        let reused = time(|| {
+            for poly in &polygons {
+                rasterizer
+                    .fill(
+                        &mut canvas,
+                        poly,
+                        color,
+                        FillRule::NonZero,
+                        BlendMode::Normal,
+                    )
+                    .unwrap();
+            }
+            black_box(&canvas);
+        });
+        println!("{:<12} {:>16?} {:>16?}", vertices, function, reused);
+    }
+}
@@ -20,13 +20,13 @@ fn main() {
             let row_offset = row_blk * blk_size;
             let col_offset = col_blk * blk_size;
             let square = Polygon::from_vec(vec![
-                (0 + col_offset) as f32,
-                (0 + row_offset) as f32,
+                col_offset as f32,
+                row_offset as f32,
                 (blk_size + col_offset) as f32,
-                (0 + row_offset) as f32,
+                row_offset as f32,
                 (blk_size + col_offset) as f32,
                 (blk_size + row_offset) as f32,
-                (0 + col_offset) as f32,
+                col_offset as f32,
                 (blk_size + row_offset) as f32,
             ])
             .unwrap();
@@ -38,7 +38,7 @@ fn main() {
                 1f64,
             );
 
-            fill_polygon(&mut canvas, &square, color, FillRule::NonZero);
+            fill_polygon(&mut canvas, &square, color, FillRule::NonZero).unwrap();
         }
     }
 
@@ -0,0 +1,141 @@
+use std::ops::Range;
+
+use palette::rgb::LinSrgba;
+
+use crate::canvas::{BlendMode, Canvas};
+use crate::error::Result;
+use crate::geometry::{Point, Primitive};
+
+// Pixel rows or columns that can get any coverage from a shape spanning `min..=max` on
+// that axis, clamped to `within`. Coverage reaches half a pixel past the outline.
+pub(crate) fn coverage_range(min: f64, max: f64, within: Range<usize>) -> Range<usize> {
+    // `as` saturates, so pixels before the canvas become 0
+    let first = ((min - 0.5).ceil() as usize).max(within.start);
+    let end = ((max + 0.5).floor() as usize)
+        .saturating_add(1)
+        .min(within.end);
+    first..end.max(first)
+}
+
+/// `color` with its alpha scaled by `coverage`.
+pub fn with_coverage(color: LinSrgba<f32>, coverage: f32) -> LinSrgba<f32> {
+    LinSrgba::new(color.red, color.green, color.blue, color.alpha * coverage)
+}
+
+/// Calls `span(row, x_start, x_end, coverage)` for the pixels in `0..width` x `rows` that
+/// the primitive covers, with `x_end` exclusive. Pixel `(x, y)` is sampled at
+/// `Point::new(x as f64, y as f64)` as in the scanline fill, and its coverage falls off
+/// linearly from 1 half a pixel inside the outline to 0 half a pixel outside. Fully
+/// covered runs come as one span, partially covered pixels one at a time.
+pub fn for_each_coverage_span(
+    primitive: &(impl Primitive + ?Sized),
+    width: usize,
+    rows: Range<usize>,
+    mut span: impl FnMut(usize, usize, usize, f32),
+) {
+    let area = primitive.area();
+    if area.is_nan() || area <= 0.0 {
+        return;
+    }
+    let bbox = primitive.bounding_box();
+    let columns = coverage_range(bbox.min.x, bbox.max.x, 0..width);
+    for row in coverage_range(bbox.min.y, bbox.max.y, rows) {
+        let mut run = None;
+        for x in columns.clone() {
+            let d = primitive.signed_distance(Point::new(x as f64, row as f64));
+            let coverage = (0.5 - d).clamp(0.0, 1.0) as f32;
+            if coverage >= 1.0 {
+                run.get_or_insert(x);
+                continue;
+            }
+            if let Some(start) = run.take() {
+                span(row, start, x, 1.0);
+            }
+            if coverage > 0.0 {
+                span(row, x, x + 1, coverage);
+            }
+        }
+        if let Some(start) = run {
+            span(row, start, columns.end, 1.0);
+        }
+    }
+}
+
+/// Fills the primitive on a `Canvas` with anti-aliased edges, compositing `color` with
+/// `mode`.
+pub fn fill_primitive(
+    canvas: &mut Canvas,
+    primitive: &(impl Primitive + ?Sized),
+    color: LinSrgba<f64>,
+    mode: BlendMode,
+) -> Result<()> {
+    primitive.check_finite()?;
+    let (width, height) = (canvas.width(), canvas.height());
+    let color: LinSrgba<f32> = color.into_format();
+    let pixels = canvas.pixels_mut();
+    for_each_coverage_span(primitive, width, 0..height, |row, lo, hi, coverage| {
+        let pixels = &mut pixels[row * width + lo..row * width + hi];
+        mode.blend_span(pixels, with_coverage(color, coverage));
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod antialias_tests {
+    use super::*;
+    use crate::canvas::CanvasSpec;
+    use crate::geometry::{Circle, Ellipse, RoundedRect};
+
+    fn coverage_sum(primitive: &impl Primitive, width: usize, height: usize) -> f64 {
+        let mut sum = 0.0;
+        for_each_coverage_span(primitive, width, 0..height, |_, lo, hi, c| {
+            sum += (hi - lo) as f64 * c as f64
+        });
+        sum
+    }
+
+    #[test]
+    fn test_coverage_matches_area() {
+        let circle = Circle::new(Point::new(20.3, 18.7), 9.6);
+        let ellipse = Ellipse::new(Point::new(20.0, 20.0), 12.0, 5.5, 0.6);
+        let rect = RoundedRect::new(Point::new(19.5, 20.5), 10.0, 7.0, 3.0, -0.4);
+        assert!((coverage_sum(&circle, 40, 40) - circle.area()).abs() < 0.5);
+        assert!((coverage_sum(&ellipse, 40, 40) - ellipse.area()).abs() < 0.5);
+        assert!((coverage_sum(&rect, 40, 40) - rect.area()).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_spans() {
+        let circle = Circle::new(Point::new(5.0, 5.0), 3.0);
+        let mut spans = Vec::new();
+        for_each_coverage_span(&circle, 20, 0..20, |row, lo, hi, c| {
+            spans.push((row, lo, hi, c))
+        });
+        // The middle row is covered from x = 2.5 to 7.5
+        let middle: Vec<_> = spans.iter().filter(|s| s.0 == 5).collect();
+        assert_eq!(middle, [&(5, 2, 3, 0.5), &(5, 3, 8, 1.0), &(5, 8, 9, 0.5)]);
+        assert_eq!(spans.first().unwrap().0, 2);
+        assert_eq!(spans.last().unwrap().0, 8);
+        // Off-canvas parts are skipped
+        let mut count = 0;
+        let off = Circle::new(Point::new(-1.0, 2.0), 3.0);
+        for_each_coverage_span(&off, 20, 1..3, |row, lo, _, _| {
+            assert!((1..3).contains(&row) && lo < 20);
+            count += 1;
+        });
+        assert!(count > 0);
+    }
+
+    #[test]
+    fn test_fill_primitive() {
+        let mut canvas = Canvas::white(CanvasSpec::new(10, 10));
+        let black = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        let circle = Circle::new(Point::new(5.0, 5.0), 3.0);
+        fill_primitive(&mut canvas, &circle, black, BlendMode::Normal).unwrap();
+        assert_eq!(canvas.get_pixel(5, 5).unwrap().red, 0.0);
+        assert_eq!(canvas.get_pixel(2, 5).unwrap().red, 0.5);
+        assert_eq!(canvas.get_pixel(0, 0).unwrap().red, 1.0);
+        let bad = Circle::new(Point::new(5.0, 5.0), f64::INFINITY);
+        assert!(fill_primitive(&mut canvas, &bad, black, BlendMode::Normal).is_err());
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::TAU;
+
+use crate::geometry::FillRule;
+use crate::geometry::{MultiPolygon, Point, Polygon, PolygonWithHoles};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BooleanOp {
+    Union,
+    Intersection,
+    // The first operand minus the second
+    Difference,
+    Xor,
+}
+
+impl BooleanOp {
+    fn apply(&self, a: bool, b: bool) -> bool {
+        match self {
+            Self::Union => a || b,
+            Self::Intersection => a && b,
+            Self::Difference => a && !b,
+            Self::Xor => a != b,
+        }
+    }
+}
+
+/// Combines two regions, each given as a set of rings filled together under `rule`,
+/// so self-intersecting and overlapping rings are handled the same way as when
+/// rasterizing. The result rings never overlap, so it can be fed back in under any rule.
+///
+/// All edges are split at their crossings and at vertices touching them, then every
+/// piece is kept if the result's inside/outside status differs between its two sides.
+/// Points closer than a relative 1e-9 are merged.
+pub fn boolean_op(
+    a: &[Polygon<f64>],
+    b: &[Polygon<f64>],
+    op: BooleanOp,
+    rule: FillRule,
+) -> MultiPolygon {
+    let edges: Vec<(Point<f64>, Point<f64>)> = a
+        .iter()
+        .chain(b)
+        .filter(|ring| ring.vertices.len() >= 3)
+        .flat_map(|ring| ring.edges().map(|e| (e.start, e.end)))
+        .filter(|(s, e)| s != e)
+        .collect();
+    if edges.is_empty() {
+        return MultiPolygon::default();
+    }
+    let scale = edges
+        .iter()
+        .fold(1f64, |m, (p, _)| m.max(p.x.abs()).max(p.y.abs()));
+    let mut points = PointSet::new(1e-9 * scale);
+
+    let splits = split_edges(&edges, points.eps);
+    let mut segments = HashSet::new();
+    for (edge, mut along) in edges.iter().zip(splits) {
+        along.push((0.0, edge.0));
+        along.push((1.0, edge.1));
+        along.sort_by(|x, y| x.0.total_cmp(&y.0));
+        let ids: Vec<usize> = along.iter().map(|&(_, p)| points.id(p)).collect();
+        for (&u, &v) in ids.iter().zip(&ids[1..]) {
+            if u != v {
+                segments.insert((u.min(v), u.max(v)));
+            }
+        }
+    }
+
+    let inside = |p: Point<f64>| {
+        let winding = |rings: &[Polygon<f64>]| rings.iter().map(|r| r.winding_number(p)).sum();
+        op.apply(rule.check(winding(a)), rule.check(winding(b)))
+    };
+    // Sorted so the output does not depend on hash order
+    let mut segments: Vec<_> = segments.into_iter().collect();
+    segments.sort_unstable();
+    let mut directed = Vec::new();
+    for (u, v) in segments {
+        let (pu, pv) = (points.get(u), points.get(v));
+        let d = pv - pu;
+        let len = d.x.hypot(d.y);
+        let offset = (len * 1e-3).min(1e-6 * scale) / len;
+        let mid = Point::new((pu.x + pv.x) / 2.0, (pu.y + pv.y) / 2.0);
+        let normal = Point::new(-d.y * offset, d.x * offset);
+        match (inside(mid + normal), inside(mid - normal)) {
+            (true, false) => directed.push((u, v)),
+            (false, true) => directed.push((v, u)),
+            _ => {}
+        }
+    }
+
+    let rings = link_rings(&directed, &points);
+    assemble(rings)
+}
+
+// Finds where each edge must be split, as (parameter along the edge, point) pairs.
+fn split_edges(edges: &[(Point<f64>, Point<f64>)], eps: f64) -> Vec<Vec<(f64, Point<f64>)>> {
+    let mut splits = vec![Vec::new(); edges.len()];
+    for (i, &(a0, a1)) in edges.iter().enumerate() {
+        let da = a1 - a0;
+        for (j, &(b0, b1)) in edges.iter().enumerate().skip(i + 1) {
+            let db = b1 - b0;
+            let denom = cross(da, db);
+            if denom.abs() <= f64::EPSILON * da.x.hypot(da.y) * db.x.hypot(db.y) {
+                continue;
+            }
+            let diff = b0 - a0;
+            let ta = cross(diff, db) / denom;
+            let tb = cross(diff, da) / denom;
+            if ta > 0.0 && ta < 1.0 && tb > 0.0 && tb < 1.0 {
+                let p = a0 + da * ta;
+                splits[i].push((ta, p));
+                splits[j].push((tb, p));
+            }
+        }
+        // Vertices touching the edge, including the ends of collinear overlaps
+        let len2 = da.x * da.x + da.y * da.y;
+        for &(v, _) in edges {
+            let t = dot(v - a0, da) / len2;
+            let off = a0 + da * t - v;
+            if t > 0.0 && t < 1.0 && off.x.hypot(off.y) <= eps {
+                splits[i].push((t, v));
+            }
+        }
+    }
+    splits
+}
+
+fn cross(a: Point<f64>, b: Point<f64>) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+fn dot(a: Point<f64>, b: Point<f64>) -> f64 {
+    a.x * b.x + a.y * b.y
+}
+
+// Deduplicates points closer than `eps`, handing out stable ids.
+struct PointSet {
+    eps: f64,
+    points: Vec<Point<f64>>,
+    grid: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl PointSet {
+    fn new(eps: f64) -> Self {
+        PointSet {
+            eps,
+            points: Vec::new(),
+            grid: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, p: Point<f64>) -> (i64, i64) {
+        (
+            (p.x / self.eps).floor() as i64,
+            (p.y / self.eps).floor() as i64,
+        )
+    }
+
+    fn id(&mut self, p: Point<f64>) -> usize {
+        let (cx, cy) = self.cell(p);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for &id in self.grid.get(&(cx + dx, cy + dy)).into_iter().flatten() {
+                    let q = self.points[id];
+                    if (q.x - p.x).hypot(q.y - p.y) <= self.eps {
+                        return id;
+                    }
+                }
+            }
+        }
+        let id = self.points.len();
+        self.points.push(p);
+        self.grid.entry((cx, cy)).or_default().push(id);
+        id
+    }
+
+    fn get(&self, id: usize) -> Point<f64> {
+        self.points[id]
+    }
+}
+
+// Joins directed edges into closed rings. Where several edges leave a vertex, the
+// sharpest turn keeping the interior on the left is taken, so regions touching at a
+// single vertex come out as separate rings.
+fn link_rings(directed: &[(usize, usize)], points: &PointSet) -> Vec<Polygon<f64>> {
+    let mut outgoing: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, &(u, _)) in directed.iter().enumerate() {
+        outgoing.entry(u).or_default().push(i);
+    }
+    let angle = |from: usize, to: usize| {
+        let d = points.get(to) - points.get(from);
+        d.y.atan2(d.x)
+    };
+
+    let mut used = vec![false; directed.len()];
+    let mut rings = Vec::new();
+    for first in 0..directed.len() {
+        if used[first] {
+            continue;
+        }
+        let start = directed[first].0;
+        let mut ids = vec![start];
+        let mut current = first;
+        let closed = loop {
+            used[current] = true;
+            let (from, to) = directed[current];
+            if to == start {
+                break true;
+            }
+            ids.push(to);
+            let back = angle(to, from);
+            let next = outgoing[&to]
+                .iter()
+                .filter(|&&e| !used[e])
+                .min_by(|&&x, &&y| {
+                    let turn = |e: usize| {
+                        let t = (back - angle(to, directed[e].1)).rem_euclid(TAU);
+                        if t == 0.0 {
+                            TAU
+                        } else {
+                            t
+                        }
+                    };
+                    turn(x).total_cmp(&turn(y))
+                });
+            match next {
+                Some(&e) => current = e,
+                None => break false,
+            }
+        };
+        if closed {
+            let vertices = drop_straight(ids.iter().map(|&id| points.get(id)).collect());
+            if vertices.len() >= 3 {
+                rings.push(Polygon { vertices });
+            }
+        }
+    }
+    rings
+}
+
+// Removes vertices in the middle of a straight run, left over from edge splitting.
+fn drop_straight(vertices: Vec<Point<f64>>) -> Vec<Point<f64>> {
+    let n = vertices.len();
+    (0..n)
+        .filter(|&i| {
+            let prev = vertices[(i + n - 1) % n];
+            let next = vertices[(i + 1) % n];
+            let (d0, d1) = (vertices[i] - prev, next - vertices[i]);
+            cross(d0, d1) != 0.0 || dot(d0, d1) <= 0.0
+        })
+        .map(|i| vertices[i])
+        .collect()
+}
+
+// Sorts rings into exteriors (counter-clockwise) and holes (clockwise), putting every
+// hole in the smallest exterior around it.
+fn assemble(rings: Vec<Polygon<f64>>) -> MultiPolygon {
+    let (exteriors, holes): (Vec<_>, Vec<_>) =
+        rings.into_iter().partition(|r| r.signed_area() > 0.0);
+    let mut polygons: Vec<PolygonWithHoles> = exteriors
+        .into_iter()
+        .map(|exterior| PolygonWithHoles::new(exterior, Vec::new()))
+        .collect();
+    for hole in holes {
+        let probe = Point::new(
+            (hole.vertices[0].x + hole.vertices[1].x) / 2.0,
+            (hole.vertices[0].y + hole.vertices[1].y) / 2.0,
+        );
+        let owner = polygons
+            .iter_mut()
+            .filter(|p| p.exterior.winding_number(probe) != 0)
+            .min_by(|x, y| x.exterior.area().total_cmp(&y.exterior.area()));
+        if let Some(owner) = owner {
+            owner.holes.push(hole);
+        }
+    }
+    MultiPolygon::new(polygons)
+}
+
+#[cfg(test)]
+mod boolean_tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn square(x: f64, y: f64, size: f64) -> Polygon<f64> {
+        Polygon::from_vec(vec![x, y, x + size, y, x + size, y + size, x, y + size]).unwrap()
+    }
+
+    fn area(a: &Polygon<f64>, b: &Polygon<f64>, op: BooleanOp) -> f64 {
+        boolean_op(
+            std::slice::from_ref(a),
+            std::slice::from_ref(b),
+            op,
+            FillRule::NonZero,
+        )
+        .area()
+    }
+
+    #[test]
+    fn test_overlapping_squares() {
+        let (a, b) = (square(0.0, 0.0, 4.0), square(2.0, 2.0, 4.0));
+        assert_relative_eq!(area(&a, &b, BooleanOp::Union), 28.0, epsilon = 1e-9);
+        assert_relative_eq!(area(&a, &b, BooleanOp::Intersection), 4.0, epsilon = 1e-9);
+        assert_relative_eq!(area(&a, &b, BooleanOp::Difference), 12.0, epsilon = 1e-9);
+        assert_relative_eq!(area(&a, &b, BooleanOp::Xor), 24.0, epsilon = 1e-9);
+        let union = boolean_op(&[a], &[b], BooleanOp::Union, FillRule::NonZero);
+        assert_eq!(union.polygons.len(), 1);
+        assert_eq!(union.polygons[0].exterior.vertices.len(), 8);
+    }
+
+    #[test]
+    fn test_difference_makes_hole() {
+        let result = boolean_op(
+            &[square(0.0, 0.0, 6.0)],
+            &[square(2.0, 2.0, 2.0)],
+            BooleanOp::Difference,
+            FillRule::NonZero,
+        );
+        assert_eq!(result.polygons.len(), 1);
+        assert_eq!(result.polygons[0].holes.len(), 1);
+        assert_relative_eq!(result.area(), 32.0, epsilon = 1e-9);
+        assert!(!result.contains(Point::new(3.0, 3.0)));
+        assert!(result.contains(Point::new(1.0, 3.0)));
+    }
+
+    #[test]
+    fn test_shared_edges_and_touching_corners() {
+        // Side by side squares merge into one rectangle
+        let union = boolean_op(
+            &[square(0.0, 0.0, 2.0)],
+            &[square(2.0, 0.0, 2.0)],
+            BooleanOp::Union,
+            FillRule::NonZero,
+        );
+        assert_eq!(union.polygons.len(), 1);
+        assert_eq!(union.polygons[0].exterior.vertices.len(), 4);
+        // Squares touching at a corner stay apart
+        let union = boolean_op(
+            &[square(0.0, 0.0, 2.0)],
+            &[square(2.0, 2.0, 2.0)],
+            BooleanOp::Union,
+            FillRule::NonZero,
+        );
+        assert_eq!(union.polygons.len(), 2);
+        assert_relative_eq!(union.area(), 8.0);
+    }
+
+    #[test]
+    fn test_fill_rule_on_self_intersecting_input() {
+        // Pentagram: the center winds twice
+        let star = Polygon::from_vec(vec![
+            0.0, 3.0, 1.76, -2.43, -2.85, 0.93, 2.85, 0.93, -1.76, -2.43,
+        ])
+        .unwrap();
+        let stars = [star];
+        let non_zero = boolean_op(&stars, &[], BooleanOp::Union, FillRule::NonZero);
+        let even_odd = boolean_op(&stars, &[], BooleanOp::Union, FillRule::EvenOdd);
+        assert!(non_zero.contains(Point::new(0.0, 0.0)));
+        assert!(!even_odd.contains(Point::new(0.0, 0.0)));
+        assert_eq!(non_zero.polygons.len(), 1);
+        assert_eq!(even_odd.polygons.len(), 5);
+        assert!(non_zero.area() > even_odd.area());
+    }
+
+    #[test]
+    fn test_disjoint_and_empty() {
+        let (a, b) = (square(0.0, 0.0, 1.0), square(5.0, 5.0, 1.0));
+        assert_eq!(area(&a, &b, BooleanOp::Intersection), 0.0);
+        assert_relative_eq!(area(&a, &b, BooleanOp::Union), 2.0);
+        assert!(boolean_op(&[], &[], BooleanOp::Union, FillRule::NonZero).is_empty());
+    }
+}
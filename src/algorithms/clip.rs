@@ -0,0 +1,373 @@
+use num_traits::{AsPrimitive, Num};
+
+use crate::geometry::FillRule;
+use crate::geometry::{Point, Polygon, Rect};
+
+fn to_f64<T: Copy + AsPrimitive<f64>>(p: &Point<T>) -> Point<f64> {
+    Point::new(p.x.as_(), p.y.as_())
+}
+
+/// Sutherland–Hodgman clipping against an axis-aligned rectangle.
+///
+/// Concave inputs may produce zero-width slivers along the rectangle border. They do not
+/// change the winding number of any point, so the result fills identically under both
+/// fill rules. The result has no vertices if the polygon misses the rectangle.
+pub fn clip_to_rect<T>(poly: &Polygon<T>, rect: &Rect<f64>) -> Polygon<f64>
+where
+    T: Copy + AsPrimitive<f64>,
+{
+    let mut clipped = Polygon::new();
+    clip_to_rect_into(poly, rect, &mut clipped, &mut Vec::new());
+    clipped
+}
+
+/// Like `clip_to_rect`, writing into `out` and using `scratch` between passes, so that
+/// clipping allocates nothing once both have grown large enough.
+pub(crate) fn clip_to_rect_into<T>(
+    poly: &Polygon<T>,
+    rect: &Rect<f64>,
+    out: &mut Polygon<f64>,
+    scratch: &mut Vec<Point<f64>>,
+) where
+    T: Copy + AsPrimitive<f64>,
+{
+    let out = &mut out.vertices;
+    out.clear();
+    out.extend(poly.vertices.iter().map(to_f64));
+    clip_half_plane(
+        out,
+        scratch,
+        |p| p.x >= rect.min.x,
+        |a, b| at_x(a, b, rect.min.x),
+    );
+    clip_half_plane(
+        scratch,
+        out,
+        |p| p.x <= rect.max.x,
+        |a, b| at_x(a, b, rect.max.x),
+    );
+    clip_half_plane(
+        out,
+        scratch,
+        |p| p.y >= rect.min.y,
+        |a, b| at_y(a, b, rect.min.y),
+    );
+    clip_half_plane(
+        scratch,
+        out,
+        |p| p.y <= rect.max.y,
+        |a, b| at_y(a, b, rect.max.y),
+    );
+}
+
+// One Sutherland–Hodgman pass from `input` into `output`. `intersect` is only called on
+// edges crossing the boundary.
+fn clip_half_plane(
+    input: &[Point<f64>],
+    output: &mut Vec<Point<f64>>,
+    inside: impl Fn(Point<f64>) -> bool,
+    intersect: impl Fn(Point<f64>, Point<f64>) -> Point<f64>,
+) {
+    output.clear();
+    let Some(&last) = input.last() else {
+        return;
+    };
+    let mut prev = last;
+    for &curr in input {
+        match (inside(prev), inside(curr)) {
+            (true, true) => output.push(curr),
+            (true, false) => output.push(intersect(prev, curr)),
+            (false, true) => {
+                output.push(intersect(prev, curr));
+                output.push(curr);
+            }
+            (false, false) => {}
+        }
+        prev = curr;
+    }
+}
+
+fn at_x(a: Point<f64>, b: Point<f64>, x: f64) -> Point<f64> {
+    let t = (x - a.x) / (b.x - a.x);
+    Point::new(x, a.y + t * (b.y - a.y))
+}
+
+fn at_y(a: Point<f64>, b: Point<f64>, y: f64) -> Point<f64> {
+    let t = (y - a.y) / (b.y - a.y);
+    Point::new(a.x + t * (b.x - a.x), y)
+}
+
+// A vertex in one of the two Greiner–Hormann lists.
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    p: Point<f64>,
+    intersect: bool,
+    // Whether walking forward from here goes inside the other polygon.
+    entry: bool,
+    // Index of the same intersection in the other list.
+    neighbor: usize,
+}
+
+// Intersection of segments a0-a1 and b0-b1 strictly inside both, as (alpha_a, alpha_b).
+fn segment_intersection(
+    a0: Point<f64>,
+    a1: Point<f64>,
+    b0: Point<f64>,
+    b1: Point<f64>,
+) -> Option<(f64, f64)> {
+    let da = a1 - a0;
+    let db = b1 - b0;
+    let denom = da.x * db.y - da.y * db.x;
+    if denom == 0.0 {
+        return None;
+    }
+    let diff = b0 - a0;
+    let alpha_a = (diff.x * db.y - diff.y * db.x) / denom;
+    let alpha_b = (diff.x * da.y - diff.y * da.x) / denom;
+    if alpha_a > 0.0 && alpha_a < 1.0 && alpha_b > 0.0 && alpha_b < 1.0 {
+        Some((alpha_a, alpha_b))
+    } else {
+        None
+    }
+}
+
+// Moves vertices of `poly` that lie on the boundary of `other` by `direction` times a
+// small distance relative to the coordinates, since Greiner–Hormann cannot classify
+// intersections at vertices.
+fn perturb_degenerate(poly: &mut [Point<f64>], other: &Polygon<f64>, direction: Point<f64>) {
+    let scale = poly
+        .iter()
+        .chain(&other.vertices)
+        .fold(1f64, |m, p| m.max(p.x.abs()).max(p.y.abs()));
+    let eps = 1e-9 * scale;
+    for p in poly.iter_mut() {
+        if other.distance_to(*p) <= eps {
+            *p = *p + direction * eps;
+        }
+    }
+}
+
+/// Greiner–Hormann intersection of two simple polygons. The result may hold several
+/// disjoint pieces. Vertices of either polygon lying within `1e-9 * scale` of the
+/// other's boundary, where `scale` is the largest absolute coordinate (at least 1), are
+/// moved by `1e-9 * scale` times `(10, 13)` or `(-13, 10)`, about `1.64e-8 * scale`,
+/// before clipping.
+pub fn clip_polygon(subject: &Polygon<f64>, clip: &Polygon<f64>) -> Vec<Polygon<f64>> {
+    if subject.vertices.len() < 3 || clip.vertices.len() < 3 {
+        return Vec::new();
+    }
+    let mut subject_pts = subject.vertices.clone();
+    perturb_degenerate(&mut subject_pts, clip, Point::new(10.0, 13.0));
+    let subject = Polygon {
+        vertices: subject_pts,
+    };
+    // Checked against the moved subject, and nudged across the subject's direction so
+    // a vertex shared by both does not land back on the other's boundary
+    let mut clip_pts = clip.vertices.clone();
+    perturb_degenerate(&mut clip_pts, &subject, Point::new(-13.0, 10.0));
+    let clip = &Polygon { vertices: clip_pts };
+
+    let (ns, nc) = (subject.vertices.len(), clip.vertices.len());
+    // intersections on each edge: (alpha, point, intersection id)
+    let mut on_subject: Vec<Vec<(f64, Point<f64>, usize)>> = vec![Vec::new(); ns];
+    let mut on_clip: Vec<Vec<(f64, Point<f64>, usize)>> = vec![Vec::new(); nc];
+    let mut count = 0;
+    for (i, subject_edge) in subject.edges().enumerate() {
+        let (a0, a1) = (subject_edge.start, subject_edge.end);
+        for (j, clip_edge) in clip.edges().enumerate() {
+            if let Some((alpha_a, alpha_b)) =
+                segment_intersection(a0, a1, clip_edge.start, clip_edge.end)
+            {
+                let p = a0 + (a1 - a0) * alpha_a;
+                on_subject[i].push((alpha_a, p, count));
+                on_clip[j].push((alpha_b, p, count));
+                count += 1;
+            }
+        }
+    }
+
+    if count == 0 {
+        // Either one polygon holds the other, or they are disjoint
+        return if clip.contains(subject.vertices[0], FillRule::EvenOdd) {
+            vec![subject]
+        } else if subject.contains(clip.vertices[0], FillRule::EvenOdd) {
+            vec![clip.clone()]
+        } else {
+            Vec::new()
+        };
+    }
+
+    let (mut subject_list, subject_pos) = build_list(&subject, &mut on_subject, count, clip);
+    let (mut clip_list, clip_pos) = build_list(clip, &mut on_clip, count, &subject);
+    for node in subject_list.iter_mut().filter(|n| n.intersect) {
+        node.neighbor = clip_pos[node.neighbor];
+    }
+    for node in clip_list.iter_mut().filter(|n| n.intersect) {
+        node.neighbor = subject_pos[node.neighbor];
+    }
+
+    let lists = [subject_list, clip_list];
+    let mut visited = [vec![false; lists[0].len()], vec![false; lists[1].len()]];
+    let mut result = Vec::new();
+    while let Some(start) = (0..lists[0].len()).find(|&i| lists[0][i].intersect && !visited[0][i]) {
+        let mut vertices = vec![lists[0][start].p];
+        let (mut list, mut current) = (0, start);
+        loop {
+            visited[list][current] = true;
+            visited[1 - list][lists[list][current].neighbor] = true;
+            let len = lists[list].len();
+            let forward = lists[list][current].entry;
+            loop {
+                current = if forward {
+                    (current + 1) % len
+                } else {
+                    (current + len - 1) % len
+                };
+                vertices.push(lists[list][current].p);
+                if lists[list][current].intersect {
+                    break;
+                }
+            }
+            current = lists[list][current].neighbor;
+            list = 1 - list;
+            if visited[list][current] {
+                break;
+            }
+        }
+        // The walk ends where it started
+        vertices.pop();
+        if vertices.len() >= 3 {
+            result.push(Polygon { vertices });
+        }
+    }
+    result
+}
+
+// Builds the vertex list of `poly` with its intersections spliced in, marking whether
+// each intersection enters `other`. Also returns the list position of each intersection id.
+fn build_list(
+    poly: &Polygon<f64>,
+    on_edge: &mut [Vec<(f64, Point<f64>, usize)>],
+    count: usize,
+    other: &Polygon<f64>,
+) -> (Vec<Node>, Vec<usize>) {
+    let mut list = Vec::with_capacity(poly.vertices.len() + count);
+    let mut pos = vec![0; count];
+    let mut inside = other.contains(poly.vertices[0], FillRule::EvenOdd);
+    for (i, edge) in on_edge.iter_mut().enumerate() {
+        list.push(Node {
+            p: poly.vertices[i],
+            intersect: false,
+            entry: false,
+            neighbor: 0,
+        });
+        edge.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for &(_, p, id) in edge.iter() {
+            pos[id] = list.len();
+            list.push(Node {
+                p,
+                intersect: true,
+                entry: !inside,
+                neighbor: id,
+            });
+            inside = !inside;
+        }
+    }
+    (list, pos)
+}
+
+/// Whether part of the polygon's bounding box lies outside `rect`, so that it needs
+/// clipping to fit.
+pub(crate) fn reaches_outside<T>(poly: &Polygon<T>, rect: &Rect<f64>) -> bool
+where
+    T: Copy + Num + PartialOrd + AsPrimitive<f64>,
+{
+    poly.bounding_box()
+        .is_some_and(|bbox| !rect.contains(to_f64(&bbox.min)) || !rect.contains(to_f64(&bbox.max)))
+}
+
+#[cfg(test)]
+mod clip_tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn square(x: f64, y: f64, size: f64) -> Polygon<f64> {
+        Polygon::from_vec(vec![x, y, x + size, y, x + size, y + size, x, y + size]).unwrap()
+    }
+
+    #[test]
+    fn test_clip_to_rect_partial() {
+        let rect = Rect::new(Point::new(0.0, 0.0), Point::new(10.0, 10.0));
+        let clipped = clip_to_rect(&square(-5.0, 5.0, 10.0), &rect);
+        assert_relative_eq!(clipped.area(), 25.0);
+        let bbox = clipped.bounding_box().unwrap();
+        assert_eq!(bbox, Rect::new(Point::new(0.0, 5.0), Point::new(5.0, 10.0)));
+    }
+
+    #[test]
+    fn test_clip_to_rect_inside_and_outside() {
+        let rect = Rect::new(Point::new(0.0, 0.0), Point::new(10.0, 10.0));
+        let inside = square(2.0, 2.0, 3.0);
+        assert_relative_eq!(clip_to_rect(&inside, &rect).area(), 9.0);
+        assert!(clip_to_rect(&square(20.0, 0.0, 3.0), &rect)
+            .vertices
+            .is_empty());
+        let int_poly = Polygon::from_vec(vec![-4, -4, 4, -4, 4, 4, -4, 4]).unwrap();
+        assert_relative_eq!(clip_to_rect(&int_poly, &rect).area(), 16.0);
+    }
+
+    #[test]
+    fn test_clip_polygon_overlap() {
+        let pieces = clip_polygon(&square(0.0, 0.0, 4.0), &square(2.0, 2.0, 4.0));
+        assert_eq!(pieces.len(), 1);
+        assert_relative_eq!(pieces[0].area(), 4.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_clip_polygon_containment() {
+        let big = square(0.0, 0.0, 10.0);
+        let small = square(2.0, 2.0, 2.0);
+        assert_relative_eq!(clip_polygon(&big, &small)[0].area(), 4.0);
+        assert_relative_eq!(clip_polygon(&small, &big)[0].area(), 4.0);
+        assert!(clip_polygon(&small, &square(20.0, 0.0, 1.0)).is_empty());
+    }
+
+    #[test]
+    fn test_clip_polygon_multiple_pieces() {
+        // A U shape clipped by a bar across both arms
+        let u = Polygon::from_vec(vec![
+            0.0, 0.0, 2.0, 0.0, 2.0, 4.0, 4.0, 4.0, 4.0, 0.0, 6.0, 0.0, 6.0, 6.0, 0.0, 6.0,
+        ])
+        .unwrap();
+        let bar = Polygon::from_vec(vec![-1.0, 1.0, 7.0, 1.0, 7.0, 3.0, -1.0, 3.0]).unwrap();
+        let pieces = clip_polygon(&u, &bar);
+        assert_eq!(pieces.len(), 2);
+        for piece in &pieces {
+            assert_relative_eq!(piece.area(), 4.0, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_clip_vertex_on_subject_edge() {
+        // The tip of the triangle touches the right side of the square
+        let triangle = Polygon::from_vec(vec![4.0, 2.0, 1.0, 1.0, 1.0, 3.0]).unwrap();
+        let pieces = clip_polygon(&square(0.0, 0.0, 4.0), &triangle);
+        let area: f64 = pieces.iter().map(|p| p.area()).sum();
+        assert_relative_eq!(area, 3.0, epsilon = 1e-6);
+
+        // Two corners of the diamond lie on the right side, so only its left half is
+        // inside
+        let diamond = Polygon::from_vec(vec![4.0, 1.0, 5.0, 2.0, 4.0, 3.0, 3.0, 2.0]).unwrap();
+        let pieces = clip_polygon(&square(0.0, 0.0, 4.0), &diamond);
+        let area: f64 = pieces.iter().map(|p| p.area()).sum();
+        assert_relative_eq!(area, 1.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_clip_polygon_shared_edge() {
+        // The right edge of the subject lies on the clip boundary
+        let pieces = clip_polygon(&square(0.0, 0.0, 4.0), &square(0.0, 2.0, 4.0));
+        let area: f64 = pieces.iter().map(|p| p.area()).sum();
+        assert_relative_eq!(area, 8.0, epsilon = 1e-6);
+    }
+}
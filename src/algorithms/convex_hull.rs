@@ -0,0 +1,107 @@
+use num_traits::AsPrimitive;
+
+use crate::geometry::{Point, Polygon};
+
+fn cross<T: Copy + AsPrimitive<f64>>(o: &Point<T>, a: &Point<T>, b: &Point<T>) -> f64 {
+    let (ox, oy): (f64, f64) = (o.x.as_(), o.y.as_());
+    (a.x.as_() - ox) * (b.y.as_() - oy) - (a.y.as_() - oy) * (b.x.as_() - ox)
+}
+
+/// Andrew's monotone chain. The hull is counter-clockwise (see `Orientation`), starts
+/// at the lowest-x point and has no collinear vertices. Fewer than three points come
+/// back when all input points are collinear. Points with a non-finite coordinate are
+/// skipped.
+pub fn convex_hull<T>(points: &[Point<T>]) -> Polygon<T>
+where
+    T: Copy + PartialOrd + AsPrimitive<f64>,
+{
+    let finite = |p: &&Point<T>| p.x.as_().is_finite() && p.y.as_().is_finite();
+    let mut sorted: Vec<Point<T>> = points.iter().filter(finite).copied().collect();
+    sorted.sort_by(|a, b| {
+        let (ax, ay): (f64, f64) = (a.x.as_(), a.y.as_());
+        let (bx, by): (f64, f64) = (b.x.as_(), b.y.as_());
+        ax.total_cmp(&bx).then(ay.total_cmp(&by))
+    });
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return Polygon { vertices: sorted };
+    }
+
+    let mut hull: Vec<Point<T>> = Vec::with_capacity(2 * sorted.len());
+    let turns_left = |hull: &[Point<T>], p: &Point<T>| {
+        cross(&hull[hull.len() - 2], &hull[hull.len() - 1], p) > 0.0
+    };
+    // Lower chain left to right
+    for p in &sorted {
+        while hull.len() >= 2 && !turns_left(&hull, p) {
+            hull.pop();
+        }
+        hull.push(*p);
+    }
+    // Upper chain right to left
+    let lower_len = hull.len() + 1;
+    for p in sorted.iter().rev().skip(1) {
+        while hull.len() >= lower_len && !turns_left(&hull, p) {
+            hull.pop();
+        }
+        hull.push(*p);
+    }
+    // The upper chain ends back at the first point
+    hull.pop();
+    Polygon { vertices: hull }
+}
+
+#[cfg(test)]
+mod convex_hull_tests {
+    use super::*;
+
+    #[test]
+    fn test_hull_of_square_with_inner_points() {
+        let points = [
+            Point::new(0, 0),
+            Point::new(2, 1),
+            Point::new(4, 0),
+            Point::new(2, 0),
+            Point::new(4, 4),
+            Point::new(1, 3),
+            Point::new(0, 4),
+            Point::new(0, 2),
+        ];
+        assert_eq!(
+            convex_hull(&points),
+            Polygon::from_vec(vec![0, 0, 4, 0, 4, 4, 0, 4]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_degenerate_hulls() {
+        assert!(convex_hull::<f64>(&[]).vertices.is_empty());
+        let same = [Point::new(1.0, 1.0); 3];
+        assert_eq!(convex_hull(&same).vertices.len(), 1);
+        let line = [
+            Point::new(0.0, 0.0),
+            Point::new(2.0, 2.0),
+            Point::new(1.0, 1.0),
+        ];
+        assert_eq!(convex_hull(&line).vertices.len(), 2);
+    }
+
+    #[test]
+    fn test_hull_ignores_nan_points() {
+        let mut points = vec![
+            Point::new(0.0, 0.0),
+            Point::new(4.0, 0.0),
+            Point::new(4.0, 4.0),
+            Point::new(0.0, 4.0),
+        ];
+        for i in 0..points.len() {
+            points.insert(2 * i, Point::new(f64::NAN, i as f64));
+        }
+        let hull = convex_hull(&points);
+        assert!(hull.vertices.iter().all(|p| p.x.is_finite()), "{:?}", hull);
+        assert_eq!(
+            hull,
+            Polygon::from_vec(vec![0.0, 0.0, 4.0, 0.0, 4.0, 4.0, 0.0, 4.0]).unwrap()
+        );
+    }
+}
@@ -1,94 +1,127 @@
-use std::collections::HashMap;
-use std::usize;
-
-use crate::geometry::Point;
-use crate::geometry::{Line, Polygon};
-use crate::nums::RoundToUsize;
-use image::RgbaImage;
-use itertools::Itertools;
-use num_traits::{AsPrimitive, FromPrimitive, Num};
+use std::ops::Range;
+
+use crate::algorithms::rasterizer::Rasterizer;
+use crate::algorithms::trace::{FillTracer, NoTrace};
+use crate::canvas::{BlendMode, Canvas};
+use crate::error::Result;
+pub use crate::geometry::FillRule;
+use crate::geometry::Polygon;
+use image::{ImageBuffer, Luma, RgbaImage};
+use num_traits::{AsPrimitive, Num};
 use palette::Srgba;
 use palette::{blend::Blend, rgb::LinSrgba};
 
-// the internal data structre for scan line algorithm
-#[derive(Debug, Clone, Copy)]
-struct ScanlineEdge {
-    y_max: usize,
-    x: f64,
-    delta_x: f64,
-    direction: i8,
+/// A run of interior pixels `x_start..x_end` on `row`, with `x_end` exclusive. The
+/// scanline fill is not anti-aliased, so every pixel of a span is fully covered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub row: usize,
+    pub x_start: usize,
+    pub x_end: usize,
 }
 
-impl ScanlineEdge {
-    fn from_line<T>(line: Line<T>) -> Option<ScanlineEdge>
-    where
-        T: Copy + Num + PartialOrd + RoundToUsize + AsPrimitive<f64>,
-    {
-        if let Some(inv_slope) = line.inv_slope() {
-            let y_max = line.y_max_point().y;
-            let Point::<T> { x, y: y_min } = line.y_min_point();
-            if y_max.ceil_to_usize() == y_min.ceil_to_usize() {
-                // Almost horizontal
-                return None;
-            }
-            Some(ScanlineEdge {
-                y_max: y_max.floor_to_usize(),
-                x: x.as_() + (y_min.ceil_to_self() - y_min).as_() * inv_slope,
-                delta_x: inv_slope,
-                direction: if line.start.y < line.end.y { 1 } else { -1 },
-            })
-        } else {
-            // horizontal
-            None
-        }
-    }
+/// Iterator over the interior spans of a polygon, in row order and left to right within
+/// a row. Created by `spans` and `spans_of_rings`.
+///
+/// It owns a `Rasterizer` and scans one row each time it runs out of spans, so a caller
+/// that stops early does not pay for the rows below.
+#[derive(Debug, Clone)]
+pub struct Spans {
+    rasterizer: Rasterizer,
+    width: usize,
+    rule: FillRule,
+    // Rows not scanned yet
+    rows: Range<usize>,
+    // The first edge of the rasterizer's edge table not yet active
+    next_edge: usize,
+    // Spans of the last scanned row, handed out from `taken` on
+    row: Vec<Span>,
+    taken: usize,
+}
 
-    fn shift_down(&mut self) {
-        self.x = self.delta_x + self.x;
-    }
+impl Iterator for Spans {
+    type Item = Span;
 
-    fn get_intersect(&self, rule: FillRule) -> (f64, i8) {
-        match rule {
-            FillRule::NonZero => (self.x, self.direction),
-            FillRule::EvenOdd => (self.x, 1),
+    fn next(&mut self) -> Option<Span> {
+        while self.taken == self.row.len() {
+            let row = self.rows.next()?;
+            self.row.clear();
+            self.taken = 0;
+            let spans = &mut self.row;
+            let more = self.rasterizer.scan_row(
+                row,
+                &mut self.next_edge,
+                self.width,
+                self.rule,
+                &mut |row, x_start, x_end| {
+                    spans.push(Span {
+                        row,
+                        x_start,
+                        x_end,
+                    })
+                },
+            );
+            if !more {
+                self.rows = 0..0;
+            }
         }
+        self.taken += 1;
+        Some(self.row[self.taken - 1])
     }
 }
 
-// New Edge Table
-type Net = HashMap<usize, Vec<ScanlineEdge>>;
-
-fn net_from_polygon<T>(poly: &Polygon<T>) -> Net
+/// Returns the interior spans of `poly` within `0..width` x `0..height`, as found by
+/// `Rasterizer`.
+pub fn spans<T>(poly: &Polygon<T>, width: usize, height: usize, rule: FillRule) -> Spans
 where
-    T: Copy + Num + PartialOrd + RoundToUsize + AsPrimitive<f64>,
+    T: Copy + Num + PartialOrd + AsPrimitive<f64>,
 {
-    let mut net = Net::new();
-    for line in poly.edges() {
-        if let Some(edge) = ScanlineEdge::from_line(line) {
-            net.entry(line.y_min_point().y.ceil_to_usize())
-                .or_insert_with(Vec::new)
-                .push(edge)
-        }
-    }
-    net
+    spans_of_rings([poly], width, height, rule)
 }
 
-// Active Edge Table
-type Aet = Vec<ScanlineEdge>;
-
-#[derive(Copy, Clone)]
-pub enum FillRule {
-    NonZero,
-    EvenOdd,
+/// Like `spans`, for the area enclosed by several rings together, e.g. the rings of a
+/// `MultiPolygon`. Their winding numbers add up, so a ring inside another is a hole
+/// under `EvenOdd`, or under `NonZero` when the two run in opposite directions. Every
+/// pixel is returned at most once.
+pub fn spans_of_rings<'a, T>(
+    rings: impl IntoIterator<Item = &'a Polygon<T>>,
+    width: usize,
+    height: usize,
+    rule: FillRule,
+) -> Spans
+where
+    T: Copy + Num + PartialOrd + AsPrimitive<f64> + 'a,
+{
+    let mut rasterizer = Rasterizer::new();
+    let rows = rasterizer.begin_scan(rings, width, height, rule);
+    Spans {
+        rasterizer,
+        width,
+        rule,
+        rows,
+        next_edge: 0,
+        row: Vec::new(),
+        taken: 0,
+    }
 }
 
-impl FillRule {
-    fn check(&self, n: i32) -> bool {
-        match self {
-            Self::NonZero => n != 0,
-            Self::EvenOdd => n % 2 != 0,
-        }
+/// Renders the polygon into a single-channel mask, 1.0 on interior pixels and 0.0
+/// elsewhere.
+pub fn coverage_mask<T>(
+    poly: &Polygon<T>,
+    width: usize,
+    height: usize,
+    rule: FillRule,
+) -> ImageBuffer<Luma<f32>, Vec<f32>>
+where
+    T: Copy + Num + PartialOrd + AsPrimitive<f64>,
+{
+    let mut mask = vec![0.0; width * height];
+    for span in spans(poly, width, height, rule) {
+        mask[span.row * width + span.x_start..span.row * width + span.x_end].fill(1.0);
     }
+    ImageBuffer::from_raw(width as u32, height as u32, mask)
+        .expect("mask has width x height pixels")
 }
 
 pub fn fill_polygon<T>(
@@ -96,65 +129,90 @@ pub fn fill_polygon<T>(
     poly: &Polygon<T>,
     polygon_color: LinSrgba<f64>,
     rule: FillRule,
-) where
-    T: Copy + Num + PartialOrd + RoundToUsize + FromPrimitive + std::fmt::Debug + AsPrimitive<f64>,
+) -> Result<()>
+where
+    T: Copy + Num + PartialOrd + AsPrimitive<f64>,
 {
-    let height = canvas.height() as usize;
+    poly.check_finite()?;
+    let (width, height) = (canvas.width() as usize, canvas.height() as usize);
 
-    // build NET
-    let net = net_from_polygon(poly);
-    //println!("net = {:?}", net);
-    //println!();
+    // 给多边形内部上色
+    for span in spans(poly, width, height, rule) {
+        for col in span.x_start..span.x_end {
+            let pixel = canvas.get_pixel_mut(col as u32, span.row as u32);
+            let bg_color: LinSrgba<f64> = <&Srgba<u8>>::from(&pixel.0).into_linear();
+            let blended = bg_color.multiply(polygon_color);
+            pixel.0 = Srgba::from_linear(blended).into();
+        }
+    }
+    Ok(())
+}
 
-    let mut aet = Aet::new();
+/// Fills the polygon on a `Canvas`, compositing `polygon_color` with `mode`.
+pub fn fill_polygon_canvas<T>(
+    canvas: &mut Canvas,
+    poly: &Polygon<T>,
+    polygon_color: LinSrgba<f64>,
+    rule: FillRule,
+    mode: BlendMode,
+) -> Result<()>
+where
+    T: Copy + Num + PartialOrd + AsPrimitive<f64>,
+{
+    fill_polygon_canvas_traced(canvas, poly, polygon_color, rule, mode, &mut NoTrace)
+}
 
-    for row in 0..height {
-        aet.iter_mut().for_each(|p| p.shift_down());
-        aet.retain(|l| l.y_max > row);
-        if let Some(new) = net.get(&row) {
-            aet.extend(new.iter().cloned());
-        }
-        if aet.len() == 0 {
-            // 快速跳过空行
-            continue;
-        }
+/// Like `fill_polygon_canvas`, reporting the fill to `tracer`.
+pub fn fill_polygon_canvas_traced<T>(
+    canvas: &mut Canvas,
+    poly: &Polygon<T>,
+    polygon_color: LinSrgba<f64>,
+    rule: FillRule,
+    mode: BlendMode,
+    tracer: &mut impl FillTracer,
+) -> Result<()>
+where
+    T: Copy + Num + PartialOrd + AsPrimitive<f64>,
+{
+    fill_rings_canvas_traced(canvas, [poly], polygon_color, rule, mode, tracer)
+}
 
-        let internal_range = aet
-            .iter()
-            .map(|e| e.get_intersect(rule))
-            // 按照交点排序
-            .sorted_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
-            .scan(0, |prefix_sum, point| {
-                *prefix_sum += point.1 as i32; // 更新前缀和
-                Some((point.0, rule.check(*prefix_sum))) // rule.check判断该点右侧是否是多边形内部
-            })
-            // non-zero rule 会产生连续的true和false
-            // 连续的T/F除了第一个以外都无意义，删除
-            .dedup_by(|p1, p2| p1.1 == p2.1)
-            .map(|p| f64::ceil(p.0) as usize)
-            .tuples::<(_, _)>();
-
-        //println!();
-
-        // 给多边形内部上色
-        for (low_idx, high_idx) in internal_range {
-            for col in low_idx..high_idx {
-                let pixel = canvas.get_pixel_mut(col as u32, row as u32);
-                let bg_color: LinSrgba<f64> = <&Srgba<u8>>::from(&pixel.0).into_linear();
-                let blended = bg_color.multiply(polygon_color);
-                println!(
-                    "bg_color = {:?}, fg_color = {:?}, mixed_color = {:?}",
-                    bg_color, polygon_color, blended
-                );
-                pixel.0 = Srgba::from_linear(blended).into();
-            }
-        }
-    }
+/// Fills the area enclosed by `rings` together (see `spans_of_rings`) on a `Canvas`,
+/// compositing `color` with `mode` once per pixel.
+pub fn fill_rings_canvas<'a, T>(
+    canvas: &mut Canvas,
+    rings: impl IntoIterator<Item = &'a Polygon<T>>,
+    color: LinSrgba<f64>,
+    rule: FillRule,
+    mode: BlendMode,
+) -> Result<()>
+where
+    T: Copy + Num + PartialOrd + AsPrimitive<f64> + 'a,
+{
+    fill_rings_canvas_traced(canvas, rings, color, rule, mode, &mut NoTrace)
+}
+
+/// Like `fill_rings_canvas`, reporting the fill to `tracer` as one polygon.
+pub fn fill_rings_canvas_traced<'a, T>(
+    canvas: &mut Canvas,
+    rings: impl IntoIterator<Item = &'a Polygon<T>>,
+    color: LinSrgba<f64>,
+    rule: FillRule,
+    mode: BlendMode,
+    tracer: &mut impl FillTracer,
+) -> Result<()>
+where
+    T: Copy + Num + PartialOrd + AsPrimitive<f64> + 'a,
+{
+    let rings: Vec<&Polygon<T>> = rings.into_iter().collect();
+    Rasterizer::new().fill_rings_traced(canvas, rings.iter().copied(), color, rule, mode, tracer)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::canvas::CanvasSpec;
+    use crate::geometry::Point;
     use approx::assert_relative_eq;
     use image::Rgba;
 
@@ -173,7 +231,7 @@ mod test {
         let poly = Polygon::from_vec(vec![0.0, 0.0, 8.0, 0.0, 8.0, 10.0, 0.0, 10.0]).unwrap();
         let black = LinSrgba::new(0f64, 0f64, 0f64, 1f64);
         let mut canvas = empty_image();
-        fill_polygon(&mut canvas, &poly, black, FillRule::NonZero);
+        fill_polygon(&mut canvas, &poly, black, FillRule::NonZero).unwrap();
         println!("{:?}", canvas);
         assert_color_at(&canvas, 0, 0, &black);
         assert_color_at(&canvas, 9, 0, &black);
@@ -186,7 +244,7 @@ mod test {
         let poly = Polygon::from_vec(vec![0, 0, 8, 0, 8, 10, 0, 10]).unwrap();
         let black = LinSrgba::new(0f64, 0f64, 0f64, 1f64);
         let mut canvas = empty_image();
-        fill_polygon(&mut canvas, &poly, black, FillRule::NonZero);
+        fill_polygon(&mut canvas, &poly, black, FillRule::NonZero).unwrap();
         println!("{:?}", canvas);
         assert_color_at(&canvas, 0, 0, &black);
         assert_color_at(&canvas, 9, 0, &black);
@@ -201,7 +259,7 @@ mod test {
         let black = LinSrgba::new(0f64, 0f64, 0f64, 1f64);
         let white = LinSrgba::new(1f64, 1f64, 1f64, 1f64);
         let mut canvas = empty_image();
-        fill_polygon(&mut canvas, &poly, black, FillRule::NonZero);
+        fill_polygon(&mut canvas, &poly, black, FillRule::NonZero).unwrap();
         println!("{:?}", canvas);
         assert_color_at(&canvas, 0, 0, &black);
         assert_color_at(&canvas, 9, 0, &white);
@@ -214,7 +272,7 @@ mod test {
         let black = LinSrgba::new(0f64, 0f64, 0f64, 1f64);
         let white = LinSrgba::new(1f64, 1f64, 1f64, 1f64);
         let mut canvas = empty_image();
-        fill_polygon(&mut canvas, &poly, black, FillRule::NonZero);
+        fill_polygon(&mut canvas, &poly, black, FillRule::NonZero).unwrap();
         println!("{:?}", canvas);
         assert_color_at(&canvas, 0, 1, &black);
         assert_color_at(&canvas, 9, 0, &white);
@@ -227,7 +285,7 @@ mod test {
         let poly = Polygon::from_vec(vec![0, 0, 20, 0, 3, 15, 13, 3, 8, 3, 18, 15]).unwrap();
         let black = LinSrgba::new(0f64, 0f64, 0f64, 1f64);
         let mut canvas = empty_image();
-        fill_polygon(&mut canvas, &poly, black, FillRule::NonZero);
+        fill_polygon(&mut canvas, &poly, black, FillRule::NonZero).unwrap();
         for row in 0..15 {
             for col in 0..20 {
                 print!("{} ", (canvas.get_pixel(col, row).0[0] > 0) as u8)
@@ -244,7 +302,7 @@ mod test {
         let black = LinSrgba::new(0f64, 0f64, 0f64, 1f64);
         let white = LinSrgba::new(1f64, 1f64, 1f64, 1f64);
         let mut canvas = empty_image();
-        fill_polygon(&mut canvas, &poly, black, FillRule::EvenOdd);
+        fill_polygon(&mut canvas, &poly, black, FillRule::EvenOdd).unwrap();
         for row in 0..15 {
             for col in 0..20 {
                 print!("{} ", (canvas.get_pixel(col, row).0[0] > 0) as u8);
@@ -263,7 +321,7 @@ mod test {
         let red = LinSrgba::new(1f64, 0f64, 0f64, 1f64);
         let green = LinSrgba::new(0f64, 1f64, 0f64, 1f64);
         let mut canvas = empty_image();
-        fill_polygon(&mut canvas, &square_left, red, FillRule::EvenOdd);
+        fill_polygon(&mut canvas, &square_left, red, FillRule::EvenOdd).unwrap();
         for row in 0..10 {
             for col in 0..30 {
                 print!("{} ", (canvas.get_pixel(col, row).0[0] > 0) as u8);
@@ -271,7 +329,252 @@ mod test {
             println!()
         }
         assert_color_at(&canvas, 5, 15, &white.multiply(red));
-        fill_polygon(&mut canvas, &square_right, green, FillRule::EvenOdd);
+        fill_polygon(&mut canvas, &square_right, green, FillRule::EvenOdd).unwrap();
         assert_color_at(&canvas, 5, 15, &white.multiply(red).multiply(green));
     }
+
+    #[test]
+    fn test_non_finite_polygon_is_rejected() {
+        let mut poly = Polygon::from_vec(vec![0.0, 0.0, 8.0, 0.0, 8.0, 10.0]).unwrap();
+        poly.add_point(Point::new(f64::NAN, 5.0));
+        let black = LinSrgba::new(0f64, 0f64, 0f64, 1f64);
+        let mut canvas = empty_image();
+        let result = fill_polygon(&mut canvas, &poly, black, FillRule::NonZero);
+        assert!(matches!(
+            result,
+            Err(crate::error::Error::NonFiniteCoordinate { index: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_canvas_matches_image() {
+        let poly = Polygon::from_vec(vec![0, 0, 20, 0, 3, 15, 13, 3, 8, 3, 18, 15]).unwrap();
+        let black = LinSrgba::new(0f64, 0f64, 0f64, 1f64);
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            let mut image = empty_image();
+            let mut canvas = Canvas::white(CanvasSpec::new(30, 20));
+            fill_polygon(&mut image, &poly, black, rule).unwrap();
+            fill_polygon_canvas(&mut canvas, &poly, black, rule, BlendMode::Multiply).unwrap();
+            for (x, y, pixel) in image.enumerate_pixels() {
+                let filled = canvas.get_pixel(x as usize, y as usize).unwrap().red == 0.0;
+                assert_eq!(pixel.0[0] == 0, filled, "pixel ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_spans_clamped_to_canvas() {
+        let poly = Polygon::from_vec(vec![20.0, 2.0, 45.0, 2.0, 45.0, 8.0, 20.0, 8.0]).unwrap();
+        let black = LinSrgba::new(0f64, 0f64, 0f64, 1f64);
+        let mut canvas = Canvas::white(CanvasSpec::new(30, 20));
+        fill_polygon_canvas(
+            &mut canvas,
+            &poly,
+            black,
+            FillRule::NonZero,
+            BlendMode::Normal,
+        )
+        .unwrap();
+        assert_eq!(canvas.get_pixel(29, 5).unwrap().red, 0.0);
+        assert_eq!(canvas.get_pixel(19, 5).unwrap().red, 1.0);
+    }
+
+    #[test]
+    fn test_fractional_bottom_row() {
+        // Row 4 lies inside [0, 4.5) and must be filled
+        let poly = Polygon::from_vec(vec![0.0, 0.0, 5.0, 0.0, 5.0, 4.5, 0.0, 4.5]).unwrap();
+        let black = LinSrgba::new(0f64, 0f64, 0f64, 1f64);
+        let white = LinSrgba::new(1f64, 1f64, 1f64, 1f64);
+        let mut canvas = empty_image();
+        fill_polygon(&mut canvas, &poly, black, FillRule::NonZero).unwrap();
+        assert_color_at(&canvas, 4, 2, &black);
+        assert_color_at(&canvas, 5, 2, &white);
+    }
+
+    // An edge covers the rows [ceil(y_min), ceil(y_max)). Edges used to end at
+    // floor(y_max), which dropped the last row whenever the bottom was fractional.
+    #[test]
+    fn test_rows_covered() {
+        for (top, bottom, rows) in [
+            (0.0, 4.0, 0..4),
+            // Was 0..4 before
+            (0.0, 4.5, 0..5),
+            (0.5, 4.0, 1..4),
+            // Was 1..4 before
+            (0.5, 4.5, 1..5),
+        ] {
+            let poly =
+                Polygon::from_vec(vec![0.0, top, 5.0, top, 5.0, bottom, 0.0, bottom]).unwrap();
+            let covered: Vec<usize> = spans(&poly, 10, 10, FillRule::NonZero)
+                .map(|span| span.row)
+                .collect();
+            assert_eq!(covered, rows.collect::<Vec<_>>(), "{} {}", top, bottom);
+        }
+    }
+
+    // Edge crossings are evaluated from the lower end point on every row. They used to
+    // be accumulated, which drifts over long edges until a span boundary moves.
+    #[test]
+    fn test_long_edges_match_contains() {
+        let (width, height) = (48, 3000);
+        let poly = Polygon::from_vec(vec![0.1, 0.0, 47.3, 2999.7, 1.0 / 3.0, 2999.9]).unwrap();
+        let mut filled = vec![false; width * height];
+        for span in spans(&poly, width, height, FillRule::NonZero) {
+            for x in span.x_start..span.x_end {
+                filled[span.row * width + x] = true;
+            }
+        }
+        for y in 0..height {
+            for x in 0..width {
+                let inside = poly.contains(Point::new(x as f64, y as f64), FillRule::NonZero);
+                assert_eq!(filled[y * width + x], inside, "({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_negative_coordinates_are_clipped() {
+        let poly = Polygon::from_vec(vec![-5, -5, 10, -5, 10, 10, -5, 10]).unwrap();
+        let black = LinSrgba::new(0f64, 0f64, 0f64, 1f64);
+        let white = LinSrgba::new(1f64, 1f64, 1f64, 1f64);
+        let mut canvas = empty_image();
+        fill_polygon(&mut canvas, &poly, black, FillRule::NonZero).unwrap();
+        assert_color_at(&canvas, 0, 0, &black);
+        assert_color_at(&canvas, 9, 9, &black);
+        assert_color_at(&canvas, 10, 5, &white);
+        assert_color_at(&canvas, 5, 10, &white);
+    }
+
+    #[test]
+    fn test_off_canvas_polygon_draws_nothing() {
+        let poly = Polygon::from_vec(vec![-20.0, -20.0, -10.0, -20.0, -10.0, -10.0]).unwrap();
+        let black = LinSrgba::new(0f64, 0f64, 0f64, 1f64);
+        let mut canvas = Canvas::white(CanvasSpec::new(30, 20));
+        let before = canvas.clone();
+        fill_polygon_canvas(
+            &mut canvas,
+            &poly,
+            black,
+            FillRule::NonZero,
+            BlendMode::Normal,
+        )
+        .unwrap();
+        assert_eq!(canvas.pixels(), before.pixels());
+    }
+
+    #[test]
+    fn test_traced_fill_stats() {
+        let poly = Polygon::from_vec(vec![2, 1, 6, 1, 6, 4, 2, 4]).unwrap();
+        let black = LinSrgba::new(0f64, 0f64, 0f64, 1f64);
+        let mut canvas = Canvas::white(CanvasSpec::new(30, 20));
+        let mut stats = crate::algorithms::trace::FillStats::default();
+        fill_polygon_canvas_traced(
+            &mut canvas,
+            &poly,
+            black,
+            FillRule::NonZero,
+            BlendMode::Normal,
+            &mut stats,
+        )
+        .unwrap();
+        assert_eq!(
+            (stats.polygons, stats.rows, stats.spans, stats.pixels),
+            (1, 3, 3, 12)
+        );
+    }
+
+    #[test]
+    fn test_spans_and_mask_match_fill() {
+        let poly = Polygon::from_vec(vec![-4, 0, 20, 0, 3, 15, 13, 3, 8, 3, 18, 25]).unwrap();
+        let black = LinSrgba::new(0f64, 0f64, 0f64, 1f64);
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            let mut canvas = Canvas::white(CanvasSpec::new(30, 20));
+            fill_polygon_canvas(&mut canvas, &poly, black, rule, BlendMode::Normal).unwrap();
+            let mask = coverage_mask(&poly, 30, 20, rule);
+            let spans: Vec<Span> = spans(&poly, 30, 20, rule).collect();
+            assert!(spans
+                .windows(2)
+                .all(|w| (w[0].row, w[0].x_end) <= (w[1].row, w[1].x_start)));
+            for y in 0..20 {
+                for x in 0..30 {
+                    let filled = canvas.get_pixel(x, y).unwrap().red == 0.0;
+                    let in_span = spans
+                        .iter()
+                        .any(|s| s.row == y && (s.x_start..s.x_end).contains(&x));
+                    assert_eq!(in_span, filled, "pixel ({}, {})", x, y);
+                    let coverage = mask.get_pixel(x as u32, y as u32).0[0];
+                    assert_eq!(coverage, if filled { 1.0 } else { 0.0 });
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_spans_match_rasterizer() {
+        let star = Polygon::from_vec(vec![0, 0, 20, 0, 3, 15, 13, 3, 8, 3, 18, 15]).unwrap();
+        let triangle = Polygon::from_vec(vec![-4, 2, 40, 9, 5, 30]).unwrap();
+        for poly in [star, triangle] {
+            for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+                let mut expected = Vec::new();
+                Rasterizer::new().for_each_span(&poly, 30, 20, rule, |row, x_start, x_end| {
+                    expected.push(Span {
+                        row,
+                        x_start,
+                        x_end,
+                    })
+                });
+                assert_eq!(spans(&poly, 30, 20, rule).collect::<Vec<_>>(), expected);
+                // Stopping early yields a prefix
+                assert_eq!(
+                    spans(&poly, 30, 20, rule).take(3).collect::<Vec<_>>(),
+                    expected[..3]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_spans_of_empty_polygon() {
+        assert_eq!(
+            spans(&Polygon::<f64>::new(), 30, 20, FillRule::NonZero).count(),
+            0
+        );
+        let mask = coverage_mask(&Polygon::<i32>::new(), 3, 2, FillRule::NonZero);
+        assert!(mask.pixels().all(|p| p.0[0] == 0.0));
+    }
+
+    #[test]
+    fn test_rings_with_holes() {
+        let square = |x: f64, y: f64, size: f64| {
+            Polygon::from_vec(vec![x, y, x + size, y, x + size, y + size, x, y + size]).unwrap()
+        };
+        let outer = square(-5.0, 2.0, 20.0);
+        let mut hole = square(4.0, 6.0, 4.0);
+        let count = |rings: &[Polygon<f64>], rule| -> usize {
+            spans_of_rings(rings, 30, 20, rule)
+                .map(|s| s.x_end - s.x_start)
+                .sum()
+        };
+        // Same direction: only `EvenOdd` cuts the hole
+        let rings = [outer.clone(), hole.clone()];
+        assert_eq!(count(&rings, FillRule::NonZero), 15 * 18);
+        assert_eq!(count(&rings, FillRule::EvenOdd), 15 * 18 - 16);
+        hole.reverse();
+        let rings = [outer, hole];
+        assert_eq!(count(&rings, FillRule::NonZero), 15 * 18 - 16);
+
+        // Every pixel blended once
+        let mut canvas = Canvas::white(CanvasSpec::new(30, 20));
+        let gray = LinSrgba::new(0.0, 0.0, 0.0, 0.5);
+        fill_rings_canvas(
+            &mut canvas,
+            &rings,
+            gray,
+            FillRule::NonZero,
+            BlendMode::Normal,
+        )
+        .unwrap();
+        assert_eq!(canvas.get_pixel(0, 2).unwrap().red, 0.5);
+        assert_eq!(canvas.get_pixel(5, 7).unwrap().red, 1.0);
+    }
 }
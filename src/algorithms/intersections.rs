@@ -0,0 +1,379 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+use num_traits::AsPrimitive;
+
+use crate::geometry::{Point, Polygon};
+
+fn cross(o: Point<f64>, a: Point<f64>, b: Point<f64>) -> f64 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+// Whether `p`, known to be collinear with segment a-b, lies within its bounding box.
+fn on_segment(a: Point<f64>, b: Point<f64>, p: Point<f64>) -> bool {
+    a.x.min(b.x) <= p.x && p.x <= a.x.max(b.x) && a.y.min(b.y) <= p.y && p.y <= a.y.max(b.y)
+}
+
+// Whether the closed segments a0-a1 and b0-b1 share any point.
+pub(crate) fn segments_touch(
+    a0: Point<f64>,
+    a1: Point<f64>,
+    b0: Point<f64>,
+    b1: Point<f64>,
+) -> bool {
+    let (d0, d1) = (cross(a0, a1, b0), cross(a0, a1, b1));
+    let (d2, d3) = (cross(b0, b1, a0), cross(b0, b1, a1));
+    if d0 * d1 < 0.0 && d2 * d3 < 0.0 {
+        return true;
+    }
+    (d0 == 0.0 && on_segment(a0, a1, b0))
+        || (d1 == 0.0 && on_segment(a0, a1, b1))
+        || (d2 == 0.0 && on_segment(b0, b1, a0))
+        || (d3 == 0.0 && on_segment(b0, b1, a1))
+}
+
+// Adjacent edges always share their common vertex. They only count as intersecting
+// when the boundary doubles back along itself.
+fn adjacent_overlap(prev: Point<f64>, shared: Point<f64>, next: Point<f64>) -> bool {
+    let (d0, d1) = (shared - prev, next - shared);
+    cross(prev, shared, next) == 0.0 && d0.x * d1.x + d0.y * d1.y < 0.0
+}
+
+// A sweep event position, ordered left to right and bottom to top at equal x.
+#[derive(Debug, Clone, Copy)]
+struct Event(Point<f64>);
+
+impl Ord for Event {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.0, other.0);
+        a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y))
+    }
+}
+
+impl PartialOrd for Event {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Event {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Event {}
+
+// An edge with its end points in event order.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    start: Point<f64>,
+    end: Point<f64>,
+}
+
+impl Segment {
+    fn new(a: Point<f64>, b: Point<f64>) -> Self {
+        if Event(a) <= Event(b) {
+            Segment { start: a, end: b }
+        } else {
+            Segment { start: b, end: a }
+        }
+    }
+
+    // Height where the sweep line through `p` crosses the segment. A vertical segment
+    // is crossed along its length, so it sits at `p` while it covers it.
+    fn y_at(&self, p: Point<f64>) -> f64 {
+        let (a, b) = (self.start, self.end);
+        if a.x == b.x {
+            p.y.clamp(a.y, b.y)
+        } else if p.x == a.x {
+            a.y
+        } else if p.x == b.x {
+            b.y
+        } else {
+            a.y + (p.x - a.x) * (b.y - a.y) / (b.x - a.x)
+        }
+    }
+
+    // Orders segments leaving the same point from bottom to top.
+    fn slope(&self) -> f64 {
+        let d = self.end - self.start;
+        if d.x == 0.0 {
+            f64::INFINITY
+        } else {
+            d.y / d.x
+        }
+    }
+
+    fn distance_to(&self, p: Point<f64>) -> f64 {
+        let (d, v) = (self.end - self.start, p - self.start);
+        let len_sq = d.x * d.x + d.y * d.y;
+        let t = if len_sq > 0.0 {
+            ((v.x * d.x + v.y * d.y) / len_sq).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let q = self.start + d * t;
+        (p.x - q.x).hypot(p.y - q.y)
+    }
+
+    // Where two touching segments first meet, unless they are parallel.
+    fn crossing(&self, other: &Segment) -> Option<Point<f64>> {
+        let (da, db) = (self.end - self.start, other.end - other.start);
+        let denom = da.x * db.y - da.y * db.x;
+        if denom == 0.0 {
+            return None;
+        }
+        let diff = other.start - self.start;
+        let t = ((diff.x * db.y - diff.y * db.x) / denom).clamp(0.0, 1.0);
+        Some(self.start + da * t)
+    }
+}
+
+// Bentley–Ottmann state. The status holds the edges crossing the sweep line from
+// bottom to top. It is a sorted `Vec`, whose linear insertions are cheap at the sizes
+// of evolved polygons.
+struct Sweep {
+    points: Vec<Point<f64>>,
+    segments: Vec<Segment>,
+    // Event positions, with the edges starting at each
+    events: BTreeMap<Event, Vec<usize>>,
+    status: Vec<usize>,
+    pairs: BTreeSet<(usize, usize)>,
+    // Computed crossings are rounded, so edges this close to an event pass through it
+    eps: f64,
+}
+
+impl Sweep {
+    fn new(points: Vec<Point<f64>>) -> Self {
+        let n = points.len();
+        let segments: Vec<Segment> = (0..n)
+            .map(|i| Segment::new(points[i], points[(i + 1) % n]))
+            .collect();
+        let mut events: BTreeMap<Event, Vec<usize>> = BTreeMap::new();
+        for (i, segment) in segments.iter().enumerate() {
+            events.entry(Event(segment.start)).or_default().push(i);
+            events.entry(Event(segment.end)).or_default();
+        }
+        let scale = points
+            .iter()
+            .fold(1f64, |m, p| m.max(p.x.abs()).max(p.y.abs()));
+        Sweep {
+            points,
+            segments,
+            events,
+            status: Vec::new(),
+            pairs: BTreeSet::new(),
+            eps: 1e-9 * scale,
+        }
+    }
+
+    fn run(mut self) -> Vec<(usize, usize)> {
+        while let Some((Event(p), starting)) = self.events.pop_first() {
+            self.handle(p, starting);
+        }
+        self.pairs.into_iter().collect()
+    }
+
+    // Every edge starting at, ending at or passing through `p` meets the others there.
+    // The edges continuing past `p` are put back in their order right of it, and the
+    // edges that become neighbors are checked for crossings further on.
+    fn handle(&mut self, p: Point<f64>, starting: Vec<usize>) {
+        let (lo, hi) = self.through(p);
+        let through: Vec<usize> = self.status.drain(lo..hi).collect();
+        let meeting: Vec<usize> = starting.iter().chain(&through).copied().collect();
+        for (k, &a) in meeting.iter().enumerate() {
+            for &b in &meeting[k + 1..] {
+                self.report(a, b);
+            }
+        }
+
+        let mut continuing: Vec<usize> = through
+            .into_iter()
+            .filter(|&i| Event(self.segments[i].end) != Event(p))
+            .chain(
+                starting
+                    .into_iter()
+                    .filter(|&i| Event(self.segments[i].end) != Event(p)),
+            )
+            .collect();
+        continuing.sort_by(|&a, &b| {
+            let (a, b) = (self.segments[a].slope(), self.segments[b].slope());
+            a.total_cmp(&b)
+        });
+        let count = continuing.len();
+        self.status.splice(lo..lo, continuing);
+
+        let (below, above) = (lo.checked_sub(1), lo + count);
+        if count == 0 {
+            if let Some(below) = below {
+                if above < self.status.len() {
+                    self.check(self.status[below], self.status[above], p);
+                }
+            }
+        } else {
+            if let Some(below) = below {
+                self.check(self.status[below], self.status[lo], p);
+            }
+            if above < self.status.len() {
+                self.check(self.status[above - 1], self.status[above], p);
+            }
+        }
+    }
+
+    // The run of status edges passing through `p`, found around where `p` would be
+    // inserted.
+    fn through(&self, p: Point<f64>) -> (usize, usize) {
+        let contains = |i: usize| self.segments[self.status[i]].distance_to(p) <= self.eps;
+        let at = self
+            .status
+            .partition_point(|&i| self.segments[i].y_at(p) < p.y);
+        let mut lo = at;
+        while lo > 0 && contains(lo - 1) {
+            lo -= 1;
+        }
+        let mut hi = at;
+        while hi < self.status.len() && contains(hi) {
+            hi += 1;
+        }
+        (lo, hi)
+    }
+
+    // Reports two new neighbors if they touch, and schedules their crossing if the
+    // sweep has not reached it yet.
+    fn check(&mut self, a: usize, b: usize, p: Point<f64>) {
+        let (sa, sb) = (self.segments[a], self.segments[b]);
+        if !segments_touch(sa.start, sa.end, sb.start, sb.end) {
+            return;
+        }
+        self.report(a, b);
+        if let Some(q) = sa.crossing(&sb) {
+            if Event(q) > Event(p) {
+                self.events.entry(Event(q)).or_default();
+            }
+        }
+    }
+
+    fn report(&mut self, a: usize, b: usize) {
+        let n = self.points.len();
+        let (lo, hi) = (a.min(b), a.max(b));
+        let points = &self.points;
+        let hit = if lo == hi {
+            false
+        } else if hi == lo + 1 {
+            adjacent_overlap(points[lo], points[hi], points[(hi + 1) % n])
+        } else if lo == 0 && hi == n - 1 {
+            adjacent_overlap(points[hi], points[0], points[1])
+        } else {
+            let (sa, sb) = (self.segments[lo], self.segments[hi]);
+            segments_touch(sa.start, sa.end, sb.start, sb.end)
+        };
+        if hit {
+            self.pairs.insert((lo, hi));
+        }
+    }
+}
+
+/// Pairs of edges `(i, j)` with `i < j` that touch or cross, where edge `i` runs from
+/// vertex `i` to vertex `i + 1`. Neighboring edges are only reported if they overlap.
+///
+/// Found with a Bentley–Ottmann sweep: edges enter and leave an ordered status at their
+/// end points, only edges that become neighbors in it are tested, and each crossing is
+/// queued as an event where its two edges swap. All edges meeting at one event point
+/// are paired with each other, which covers shared vertices and overlaps.
+pub fn self_intersections<T>(poly: &Polygon<T>) -> Vec<(usize, usize)>
+where
+    T: Copy + AsPrimitive<f64>,
+{
+    if poly.vertices.len() < 3 {
+        return Vec::new();
+    }
+    let points: Vec<Point<f64>> = poly
+        .vertices
+        .iter()
+        .map(|p| Point::new(p.x.as_(), p.y.as_()))
+        .collect();
+    Sweep::new(points).run()
+}
+
+#[cfg(test)]
+mod intersections_tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_polygons() {
+        let square = Polygon::from_vec(vec![0, 0, 4, 0, 4, 4, 0, 4]).unwrap();
+        assert!(self_intersections(&square).is_empty());
+        let concave = Polygon::from_vec(vec![0, 0, 4, 0, 2, 1, 4, 4, 0, 4]).unwrap();
+        assert!(self_intersections(&concave).is_empty());
+    }
+
+    #[test]
+    fn test_bowtie() {
+        let bowtie = Polygon::from_vec(vec![0.0, 0.0, 4.0, 4.0, 4.0, 0.0, 0.0, 4.0]).unwrap();
+        assert_eq!(self_intersections(&bowtie), vec![(0, 2)]);
+    }
+
+    #[test]
+    fn test_touching_and_overlapping() {
+        // Vertex 4 touches edge 1 from the inside
+        let touching = Polygon::from_vec(vec![0, 0, 4, 0, 4, 4, 2, 4, 4, 2, 0, 4]).unwrap();
+        assert_eq!(self_intersections(&touching), vec![(1, 3), (1, 4)]);
+        // The boundary doubles back on itself along the x axis
+        let spike = Polygon::from_vec(vec![0, 0, 4, 0, 2, 0, 2, 3]).unwrap();
+        assert_eq!(self_intersections(&spike), vec![(0, 1), (0, 2)]);
+    }
+
+    // Every pair of edges, tested directly with the same rules.
+    fn brute_force(poly: &Polygon<f64>) -> Vec<(usize, usize)> {
+        let n = poly.vertices.len();
+        let p = &poly.vertices;
+        let mut pairs = Vec::new();
+        for i in 0..n {
+            for j in i + 1..n {
+                let hit = if j == i + 1 {
+                    adjacent_overlap(p[i], p[j], p[(j + 1) % n])
+                } else if i == 0 && j == n - 1 {
+                    adjacent_overlap(p[j], p[0], p[1])
+                } else {
+                    segments_touch(p[i], p[i + 1], p[j], p[(j + 1) % n])
+                };
+                if hit {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        pairs
+    }
+
+    #[test]
+    fn test_sweep_matches_brute_force() {
+        let mut rng = crate::utils::Rng::new(11);
+        for i in 0..2000 {
+            let count = 3 + i % 12;
+            // A coarse grid for many shared points, vertical edges and overlaps
+            let coord = |rng: &mut crate::utils::Rng| {
+                if i % 2 == 0 {
+                    rng.below(6) as f64
+                } else {
+                    rng.range_f64(-10.0, 10.0)
+                }
+            };
+            let vertices = (0..count)
+                .map(|_| Point::new(coord(&mut rng), coord(&mut rng)))
+                .collect();
+            let poly = Polygon { vertices };
+            assert_eq!(self_intersections(&poly), brute_force(&poly), "{:?}", poly);
+        }
+    }
+
+    #[test]
+    fn test_pentagram() {
+        let star = Polygon::from_vec(vec![
+            0.0, 3.0, 1.76, -2.43, -2.85, 0.93, 2.85, 0.93, -1.76, -2.43,
+        ])
+        .unwrap();
+        // Every edge crosses both edges it is not adjacent to
+        assert_eq!(self_intersections(&star).len(), 5);
+    }
+}
@@ -1 +1,12 @@
+pub mod antialias;
+pub mod boolean;
+pub mod clip;
+pub mod convex_hull;
 pub mod fill_polygon;
+pub mod intersections;
+pub mod rasterizer;
+pub mod similarity;
+pub mod simplify;
+pub mod stroke;
+pub mod trace;
+pub mod triangulate;
@@ -0,0 +1,659 @@
+use std::ops::Range;
+
+use num_traits::{AsPrimitive, Num};
+use palette::rgb::LinSrgba;
+
+use crate::algorithms::clip::{clip_to_rect_into, reaches_outside};
+use crate::algorithms::trace::{FillTracer, NoTrace};
+use crate::canvas::{BlendMode, Canvas};
+use crate::error::Result;
+use crate::geometry::FillRule;
+use crate::geometry::{Line, Point, Polygon, Rect};
+
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    // Rows `first_row..end_row` cross the edge
+    first_row: usize,
+    end_row: usize,
+    // The end point with smaller y
+    x0: f64,
+    y0: f64,
+    inv_slope: f64,
+    x: f64,
+    direction: i32,
+}
+
+impl Edge {
+    // The edge clamped to `rows`, or `None` if it crosses none of them.
+    fn new<T>(line: Line<T>, rows: &Range<usize>, rule: FillRule) -> Option<Edge>
+    where
+        T: Copy + Num + PartialOrd + AsPrimitive<f64>,
+    {
+        let inv_slope = line.inv_slope()?;
+        let (low, high) = if line.start.y <= line.end.y {
+            (line.start, line.end)
+        } else {
+            (line.end, line.start)
+        };
+        let (y0, y1): (f64, f64) = (low.y.as_(), high.y.as_());
+        // `as` saturates, so rows above the canvas become 0
+        let first_row = (y0.ceil() as usize).max(rows.start);
+        let end_row = (y1.ceil() as usize).min(rows.end);
+        if first_row >= end_row {
+            return None;
+        }
+        Some(Edge {
+            first_row,
+            end_row,
+            x0: low.x.as_(),
+            y0,
+            inv_slope,
+            x: 0.0,
+            direction: match rule {
+                FillRule::NonZero if line.start.y > line.end.y => -1,
+                _ => 1,
+            },
+        })
+    }
+
+    fn x_at(&self, row: usize) -> f64 {
+        self.x0 + (row as f64 - self.y0) * self.inv_slope
+    }
+}
+
+/// The crate's scanline rasterizer, behind `spans`, `fill_polygon_canvas` and
+/// `Drawing::render`. It keeps its edge tables and clipping buffers between calls, so
+/// once they have grown to fit the largest polygon, filling allocates nothing.
+///
+/// Polygons reaching off the canvas are clipped to it first, so the spans do not depend
+/// on how far off the canvas a polygon reaches, and a single convex polygon is walked
+/// down its two sides without the edge table.
+#[derive(Debug, Default, Clone)]
+pub struct Rasterizer {
+    // Every edge of the current polygon, sorted by first row
+    edges: Vec<Edge>,
+    // Edges crossing the current row, sorted by x
+    active: Vec<Edge>,
+    // The current ring clipped to the canvas, and the buffer between clipping passes
+    clipped: Polygon<f64>,
+    scratch: Vec<Point<f64>>,
+}
+
+impl Rasterizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calls `span(row, x_start, x_end)` for every interior span in `0..width` x
+    /// `0..height`, with `x_end` exclusive.
+    pub fn for_each_span<T>(
+        &mut self,
+        poly: &Polygon<T>,
+        width: usize,
+        height: usize,
+        rule: FillRule,
+        span: impl FnMut(usize, usize, usize),
+    ) where
+        T: Copy + Num + PartialOrd + AsPrimitive<f64>,
+    {
+        self.for_each_span_in_rows(poly, width, height, 0..height, rule, span)
+    }
+
+    /// Like `for_each_span`, only scanning `rows`. Each row gets the same spans as when
+    /// scanning the whole canvas, so a canvas can be filled band by band.
+    pub fn for_each_span_in_rows<T>(
+        &mut self,
+        poly: &Polygon<T>,
+        width: usize,
+        height: usize,
+        rows: Range<usize>,
+        rule: FillRule,
+        span: impl FnMut(usize, usize, usize),
+    ) where
+        T: Copy + Num + PartialOrd + AsPrimitive<f64>,
+    {
+        self.for_each_span_of_rings([poly], width, height, rows, rule, span)
+    }
+
+    /// Like `for_each_span_in_rows`, for the area enclosed by several rings together.
+    /// The winding numbers of the rings add up, so a ring inside another is a hole under
+    /// `EvenOdd`, or under `NonZero` when the two run in opposite directions.
+    pub fn for_each_span_of_rings<'a, T>(
+        &mut self,
+        rings: impl IntoIterator<Item = &'a Polygon<T>>,
+        width: usize,
+        height: usize,
+        rows: Range<usize>,
+        rule: FillRule,
+        mut span: impl FnMut(usize, usize, usize),
+    ) where
+        T: Copy + Num + PartialOrd + AsPrimitive<f64> + 'a,
+    {
+        let rows = rows.start..rows.end.min(height);
+        let bounds = Rect::new(
+            Point::new(0.0, 0.0),
+            Point::new(width as f64, height as f64),
+        );
+        self.edges.clear();
+        self.active.clear();
+        let mut rings = rings.into_iter();
+        let first = rings.next();
+        let second = rings.next();
+        if let (Some(ring), None) = (first, second) {
+            if reaches_outside(ring, &bounds) {
+                let mut clipped = std::mem::take(&mut self.clipped);
+                clip_to_rect_into(ring, &bounds, &mut clipped, &mut self.scratch);
+                self.ring_spans(&clipped, width, &rows, rule, &mut span);
+                self.clipped = clipped;
+            } else {
+                self.ring_spans(ring, width, &rows, rule, &mut span);
+            }
+            return;
+        }
+        self.push_clipped_edges(
+            first.into_iter().chain(second).chain(rings),
+            &bounds,
+            &rows,
+            rule,
+        );
+        self.scan(width, &rows, rule, &mut span);
+    }
+
+    /// Builds the edge table of `rings` for scanning them one row at a time with
+    /// `scan_row`, and returns the rows that may hold spans.
+    pub(crate) fn begin_scan<'a, T>(
+        &mut self,
+        rings: impl IntoIterator<Item = &'a Polygon<T>>,
+        width: usize,
+        height: usize,
+        rule: FillRule,
+    ) -> Range<usize>
+    where
+        T: Copy + Num + PartialOrd + AsPrimitive<f64> + 'a,
+    {
+        let rows = 0..height;
+        let bounds = Rect::new(
+            Point::new(0.0, 0.0),
+            Point::new(width as f64, height as f64),
+        );
+        self.edges.clear();
+        self.active.clear();
+        self.push_clipped_edges(rings, &bounds, &rows, rule);
+        self.edges.sort_unstable_by_key(|e| e.first_row);
+        match self.edges.first() {
+            Some(first) => first.first_row..rows.end,
+            None => 0..0,
+        }
+    }
+
+    // Clipping each ring to the canvas keeps the winding number of every pixel on it.
+    fn push_clipped_edges<'a, T>(
+        &mut self,
+        rings: impl IntoIterator<Item = &'a Polygon<T>>,
+        bounds: &Rect<f64>,
+        rows: &Range<usize>,
+        rule: FillRule,
+    ) where
+        T: Copy + Num + PartialOrd + AsPrimitive<f64> + 'a,
+    {
+        for ring in rings {
+            if reaches_outside(ring, bounds) {
+                clip_to_rect_into(ring, bounds, &mut self.clipped, &mut self.scratch);
+                push_edges(&mut self.edges, &self.clipped, rows, rule);
+            } else {
+                push_edges(&mut self.edges, ring, rows, rule);
+            }
+        }
+    }
+
+    // Spans of a single ring, walking convex ones without the edge table.
+    fn ring_spans<T>(
+        &mut self,
+        ring: &Polygon<T>,
+        width: usize,
+        rows: &Range<usize>,
+        rule: FillRule,
+        span: &mut impl FnMut(usize, usize, usize),
+    ) where
+        T: Copy + Num + PartialOrd + AsPrimitive<f64>,
+    {
+        if ring.is_convex() {
+            convex_spans(ring, width, rows, span);
+        } else {
+            push_edges(&mut self.edges, ring, rows, rule);
+            self.scan(width, rows, rule, span);
+        }
+    }
+
+    // Scans the edge table.
+    fn scan(
+        &mut self,
+        width: usize,
+        rows: &Range<usize>,
+        rule: FillRule,
+        span: &mut impl FnMut(usize, usize, usize),
+    ) {
+        self.edges.sort_unstable_by_key(|e| e.first_row);
+
+        let Some(first) = self.edges.first().map(|e| e.first_row) else {
+            return;
+        };
+        let mut next = 0;
+        for row in first..rows.end {
+            if !self.scan_row(row, &mut next, width, rule, span) {
+                break;
+            }
+        }
+    }
+
+    /// Calls `span` for the spans of `row`, the row after the one scanned last. `next`
+    /// is the first edge of the sorted edge table not yet active, starting from 0.
+    /// Returns false once no edge reaches `row` or any row below it.
+    pub(crate) fn scan_row(
+        &mut self,
+        row: usize,
+        next: &mut usize,
+        width: usize,
+        rule: FillRule,
+        span: &mut impl FnMut(usize, usize, usize),
+    ) -> bool {
+        self.active.retain(|e| e.end_row > row);
+        while *next < self.edges.len() && self.edges[*next].first_row <= row {
+            self.active.push(self.edges[*next]);
+            *next += 1;
+        }
+        if self.active.is_empty() {
+            return *next < self.edges.len();
+        }
+        for e in &mut self.active {
+            e.x = e.x_at(row);
+        }
+        // Insertion sort, as the order barely changes from one row to the next
+        for i in 1..self.active.len() {
+            let mut j = i;
+            while j > 0 && self.active[j - 1].x.total_cmp(&self.active[j].x).is_gt() {
+                self.active.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+
+        let (mut winding, mut inside, mut start) = (0, false, 0.0);
+        for e in &self.active {
+            winding += e.direction;
+            let now = rule.check(winding);
+            if now == inside {
+                continue;
+            }
+            if now {
+                start = e.x;
+            } else {
+                emit_span(row, start, e.x, width, span);
+            }
+            inside = now;
+        }
+        true
+    }
+
+    /// Fills the polygon on a `Canvas`, compositing `color` with `mode`.
+    pub fn fill<T>(
+        &mut self,
+        canvas: &mut Canvas,
+        poly: &Polygon<T>,
+        color: LinSrgba<f64>,
+        rule: FillRule,
+        mode: BlendMode,
+    ) -> Result<()>
+    where
+        T: Copy + Num + PartialOrd + AsPrimitive<f64>,
+    {
+        self.fill_traced(canvas, poly, color, rule, mode, &mut NoTrace)
+    }
+
+    /// Like `fill`, reporting the fill to `tracer`.
+    pub fn fill_traced<T>(
+        &mut self,
+        canvas: &mut Canvas,
+        poly: &Polygon<T>,
+        color: LinSrgba<f64>,
+        rule: FillRule,
+        mode: BlendMode,
+        tracer: &mut impl FillTracer,
+    ) -> Result<()>
+    where
+        T: Copy + Num + PartialOrd + AsPrimitive<f64>,
+    {
+        self.fill_rings_traced(canvas, [poly], color, rule, mode, tracer)
+    }
+
+    /// Fills the area enclosed by `rings` in a single pass (see `for_each_span_of_rings`),
+    /// so that every pixel is composited once even where rings overlap.
+    pub fn fill_rings<'a, T>(
+        &mut self,
+        canvas: &mut Canvas,
+        rings: impl IntoIterator<Item = &'a Polygon<T>> + Clone,
+        color: LinSrgba<f64>,
+        rule: FillRule,
+        mode: BlendMode,
+    ) -> Result<()>
+    where
+        T: Copy + Num + PartialOrd + AsPrimitive<f64> + 'a,
+    {
+        self.fill_rings_traced(canvas, rings, color, rule, mode, &mut NoTrace)
+    }
+
+    /// Like `fill_rings`, reporting the fill to `tracer` as one polygon.
+    pub fn fill_rings_traced<'a, T>(
+        &mut self,
+        canvas: &mut Canvas,
+        rings: impl IntoIterator<Item = &'a Polygon<T>> + Clone,
+        color: LinSrgba<f64>,
+        rule: FillRule,
+        mode: BlendMode,
+        tracer: &mut impl FillTracer,
+    ) -> Result<()>
+    where
+        T: Copy + Num + PartialOrd + AsPrimitive<f64> + 'a,
+    {
+        for ring in rings.clone() {
+            ring.check_finite()?;
+        }
+        tracer.begin_polygon();
+        let (width, height) = (canvas.width(), canvas.height());
+        let color: LinSrgba<f32> = color.into_format();
+        let pixels = canvas.pixels_mut();
+        self.for_each_span_of_rings(rings, width, height, 0..height, rule, |row, lo, hi| {
+            tracer.span(row, lo, hi);
+            mode.blend_span(&mut pixels[row * width + lo..row * width + hi], color);
+        });
+        Ok(())
+    }
+}
+
+fn push_edges<T>(edges: &mut Vec<Edge>, ring: &Polygon<T>, rows: &Range<usize>, rule: FillRule)
+where
+    T: Copy + Num + PartialOrd + AsPrimitive<f64>,
+{
+    edges.extend(ring.edges().filter_map(|line| Edge::new(line, rows, rule)));
+}
+
+// Every row of a convex polygon crosses exactly one edge on each side, so the two sides
+// are walked down from the top vertex without any edge table.
+fn convex_spans<T>(
+    poly: &Polygon<T>,
+    width: usize,
+    rows: &Range<usize>,
+    span: &mut impl FnMut(usize, usize, usize),
+) where
+    T: Copy + Num + PartialOrd + AsPrimitive<f64>,
+{
+    let v = &poly.vertices;
+    let n = v.len();
+    let y = |i: usize| -> f64 { v[i].y.as_() };
+    let top = (0..n).min_by(|&a, &b| y(a).total_cmp(&y(b))).unwrap_or(0);
+    let forward = |k: usize| Line::new(v[(top + k) % n], v[(top + k + 1) % n]);
+    let backward = |k: usize| Line::new(v[(top + n - k) % n], v[(top + 2 * n - k - 1) % n]);
+    let (mut k_forward, mut k_backward) = (0, 0);
+    for row in rows.clone() {
+        let (Some(a), Some(b)) = (
+            side_edge(&mut k_forward, n, forward, rows, row),
+            side_edge(&mut k_backward, n, backward, rows, row),
+        ) else {
+            break;
+        };
+        if a.first_row > row || b.first_row > row {
+            continue;
+        }
+        // Intersections are computed as on the general path, giving the same pixels,
+        // and the fill rule does not matter
+        let (xa, xb) = (a.x_at(row), b.x_at(row));
+        let (lo, hi) = if xa <= xb { (xa, xb) } else { (xb, xa) };
+        emit_span(row, lo, hi, width, span);
+    }
+}
+
+// Finds the edge of one side of a convex polygon active at `row`. `edge(k)` is the k-th
+// edge walking down that side from the top vertex and `k` only ever moves forward.
+fn side_edge<T>(
+    k: &mut usize,
+    n: usize,
+    edge: impl Fn(usize) -> Line<T>,
+    rows: &Range<usize>,
+    row: usize,
+) -> Option<Edge>
+where
+    T: Copy + Num + PartialOrd + AsPrimitive<f64>,
+{
+    while *k < n {
+        match Edge::new(edge(*k), rows, FillRule::EvenOdd) {
+            Some(e) if row < e.end_row => return Some(e),
+            _ => *k += 1,
+        }
+    }
+    None
+}
+
+// Calls `span` for the pixels from `start` to `end` on `row`, if any.
+fn emit_span(
+    row: usize,
+    start: f64,
+    end: f64,
+    width: usize,
+    span: &mut impl FnMut(usize, usize, usize),
+) {
+    let lo = (start.ceil() as usize).min(width);
+    let hi = (end.ceil() as usize).min(width);
+    if lo < hi {
+        span(row, lo, hi);
+    }
+}
+
+#[cfg(test)]
+mod rasterizer_tests {
+    use super::*;
+    use crate::canvas::CanvasSpec;
+    use crate::utils::Rng;
+
+    // Whether every pixel is filled exactly where the polygon contains its sample point.
+    fn assert_matches_contains(rasterizer: &mut Rasterizer, poly: &Polygon<f64>, rule: FillRule) {
+        let (width, height) = (32, 24);
+        let mut filled = vec![false; width * height];
+        rasterizer.for_each_span(poly, width, height, rule, |row, lo, hi| {
+            filled[row * width + lo..row * width + hi].fill(true)
+        });
+        for y in 0..height {
+            for x in 0..width {
+                let inside = poly.contains(Point::new(x as f64, y as f64), rule);
+                assert_eq!(filled[y * width + x], inside, "({}, {}) {:?}", x, y, poly);
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_contains() {
+        let mut rng = Rng::new(3);
+        let mut rasterizer = Rasterizer::new();
+        for i in 0..300 {
+            let mut poly = Polygon::new();
+            for _ in 0..3 + i % 8 {
+                let (x, y) = (rng.range_f64(0.0, 32.0), rng.range_f64(0.0, 24.0));
+                if i % 2 == 0 {
+                    poly.add_point(Point::new(x.round(), y.round()));
+                } else {
+                    poly.add_point(Point::new(x, y));
+                }
+            }
+            // Every other polygon is made convex to cover the convex walk too
+            if i % 4 < 2 {
+                poly = poly.convex_hull();
+            }
+            for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+                assert_matches_contains(&mut rasterizer, &poly, rule);
+            }
+        }
+    }
+
+    #[test]
+    fn test_off_canvas_matches_contains() {
+        let mut rng = Rng::new(8);
+        let mut rasterizer = Rasterizer::new();
+        for i in 0..500 {
+            let mut poly = Polygon::new();
+            for _ in 0..3 + i % 6 {
+                poly.add_point(Point::new(
+                    rng.range_f64(-40.0, 70.0),
+                    rng.range_f64(-30.0, 55.0),
+                ));
+            }
+            if i % 4 < 2 {
+                poly = poly.convex_hull();
+            }
+            for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+                assert_matches_contains(&mut rasterizer, &poly, rule);
+            }
+        }
+    }
+
+    // Collects the spans of the general and the convex walk for one polygon.
+    fn both_walks(poly: &Polygon<f64>, rule: FillRule) -> (Vec<[usize; 3]>, Vec<[usize; 3]>) {
+        let rows = 0..40;
+        let mut rasterizer = Rasterizer::new();
+        let mut general = Vec::new();
+        push_edges(&mut rasterizer.edges, poly, &rows, rule);
+        rasterizer.scan(40, &rows, rule, &mut |row, lo, hi| {
+            general.push([row, lo, hi])
+        });
+        let mut convex = Vec::new();
+        convex_spans(poly, 40, &rows, &mut |row, lo, hi| {
+            convex.push([row, lo, hi])
+        });
+        (general, convex)
+    }
+
+    #[test]
+    fn test_convex_walk_matches_general() {
+        let mut rng = Rng::new(7);
+        for i in 0..500 {
+            // Integer-aligned vertices for some polygons, to hit exact row and column hits
+            let count = 3 + i % 6;
+            let points: Vec<Point<f64>> = (0..count)
+                .map(|_| {
+                    let (x, y) = (rng.range_f64(0.0, 40.0), rng.range_f64(0.0, 40.0));
+                    if i % 2 == 0 {
+                        Point::new(x.round(), y.round())
+                    } else {
+                        Point::new(x, y)
+                    }
+                })
+                .collect();
+            let mut hull = crate::algorithms::convex_hull::convex_hull(&points);
+            if !hull.is_convex() {
+                continue;
+            }
+            if i % 3 == 0 {
+                hull.reverse();
+            }
+            for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+                let (general, convex) = both_walks(&hull, rule);
+                assert_eq!(general, convex, "{:?}", hull);
+            }
+        }
+    }
+
+    #[test]
+    fn test_convex_walk_with_collinear_and_flat_edges() {
+        let poly = Polygon::from_vec(vec![
+            2.0, 2.0, 6.0, 2.0, 10.0, 2.0, 12.5, 7.5, 10.0, 12.0, 2.0, 12.0, 2.0, 7.0,
+        ])
+        .unwrap();
+        assert!(poly.is_convex());
+        let (general, convex) = both_walks(&poly, FillRule::NonZero);
+        assert!(!general.is_empty());
+        assert_eq!(general, convex);
+    }
+
+    #[test]
+    fn test_off_canvas_and_negative_integers() {
+        let mut rasterizer = Rasterizer::new();
+        let poly = Polygon::from_vec(vec![-5, -5, 10, -5, 10, 10, -5, 10]).unwrap();
+        let mut spans = Vec::new();
+        rasterizer.for_each_span(&poly, 8, 20, FillRule::NonZero, |row, lo, hi| {
+            spans.push((row, lo, hi))
+        });
+        assert_eq!(spans, (0..10).map(|row| (row, 0, 8)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_buffers_are_reused() {
+        let mut rasterizer = Rasterizer::new();
+        let mut canvas = Canvas::white(CanvasSpec::new(16, 16));
+        let poly = Polygon::from_vec(vec![0.0, 0.0, 12.0, 2.0, 3.0, 15.0, 14.0, 9.0]).unwrap();
+        // Reaching off the canvas, so it is clipped first
+        let off_canvas =
+            Polygon::from_vec(vec![-6.0, 0.0, 12.0, 2.0, 3.0, 25.0, 14.0, 9.0]).unwrap();
+        let black = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        let mut fill = |rasterizer: &mut Rasterizer| {
+            for poly in [&poly, &off_canvas] {
+                rasterizer
+                    .fill(
+                        &mut canvas,
+                        poly,
+                        black,
+                        FillRule::EvenOdd,
+                        BlendMode::Normal,
+                    )
+                    .unwrap();
+            }
+        };
+        let capacities = |r: &Rasterizer| {
+            [
+                r.edges.capacity(),
+                r.active.capacity(),
+                r.clipped.vertices.capacity(),
+                r.scratch.capacity(),
+            ]
+        };
+        fill(&mut rasterizer);
+        let before = capacities(&rasterizer);
+        assert!(before.iter().all(|&c| c > 0));
+        for _ in 0..10 {
+            fill(&mut rasterizer);
+        }
+        assert_eq!(capacities(&rasterizer), before);
+    }
+
+    #[test]
+    fn test_rings_fill_once() {
+        let mut rasterizer = Rasterizer::new();
+        let outer = Polygon::from_vec(vec![0, 0, 10, 0, 10, 10, 0, 10]).unwrap();
+        let inner = Polygon::from_vec(vec![2, 2, 8, 2, 8, 8, 2, 8]).unwrap();
+        let mut hole = inner.clone();
+        hole.reverse();
+        let half_black = LinSrgba::new(0.0, 0.0, 0.0, 0.5);
+        let mut canvas = Canvas::white(CanvasSpec::new(12, 12));
+        let rings = [outer.clone(), inner];
+        rasterizer
+            .fill_rings(
+                &mut canvas,
+                &rings,
+                half_black,
+                FillRule::NonZero,
+                BlendMode::Normal,
+            )
+            .unwrap();
+        // Overlapping rings of the same direction are composited once
+        assert_eq!(canvas.get_pixel(1, 1), canvas.get_pixel(5, 5));
+        assert_eq!(canvas.get_pixel(5, 5).unwrap().red, 0.5);
+        let mut canvas = Canvas::white(CanvasSpec::new(12, 12));
+        rasterizer
+            .fill_rings(
+                &mut canvas,
+                &[outer, hole],
+                half_black,
+                FillRule::NonZero,
+                BlendMode::Normal,
+            )
+            .unwrap();
+        assert_eq!(canvas.get_pixel(1, 1).unwrap().red, 0.5);
+        assert_eq!(canvas.get_pixel(5, 5).unwrap().red, 1.0);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::canvas::Canvas;
+use crate::error::{Error, Result};
+
+pub trait ImageSimilarity {
+    /// Dissimilarity between two canvases of the same size. Lower is more similar,
+    /// and identical canvases score 0.
+    fn distance(&self, a: &Canvas, b: &Canvas) -> Result<f64>;
+}
+
+// Mean squared error over all premultiplied RGBA components.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Mse;
+
+pub(crate) fn check_same_spec(a: &Canvas, b: &Canvas) -> Result<()> {
+    let (a, b) = (a.get_spec(), b.get_spec());
+    if a != b {
+        return Err(Error::ShapeMismatch {
+            expected: vec![a.height, a.width],
+            actual: vec![b.height, b.width],
+        });
+    }
+    Ok(())
+}
+
+impl ImageSimilarity for Mse {
+    fn distance(&self, a: &Canvas, b: &Canvas) -> Result<f64> {
+        check_same_spec(a, b)?;
+        let count = a.pixels().len() * 4;
+        if count == 0 {
+            return Ok(0.0);
+        }
+        let sum: f64 = a
+            .pixels()
+            .iter()
+            .zip(b.pixels())
+            .map(|(p, q)| {
+                let d = [
+                    p.red - q.red,
+                    p.green - q.green,
+                    p.blue - q.blue,
+                    p.alpha - q.alpha,
+                ];
+                d.iter().map(|&c| (c as f64) * (c as f64)).sum::<f64>()
+            })
+            .sum();
+        Ok(sum / count as f64)
+    }
+}
+
+#[cfg(test)]
+mod similarity_tests {
+    use super::*;
+    use crate::canvas::CanvasSpec;
+    use approx::assert_relative_eq;
+    use palette::rgb::LinSrgba;
+
+    #[test]
+    fn test_mse_identical_is_zero() {
+        let a = Canvas::from_wh(5, 4);
+        assert_eq!(Mse.distance(&a, &a.clone()).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_mse_value() {
+        let spec = CanvasSpec::new(2, 1);
+        let a = Canvas::from_color(spec, LinSrgba::new(1.0, 1.0, 1.0, 1.0));
+        let mut b = a.clone();
+        b.put_pixel(0, 0, LinSrgba::new(0.0, 1.0, 1.0, 1.0))
+            .unwrap();
+        // One squared error of 1 among 8 components
+        assert_relative_eq!(Mse.distance(&a, &b).unwrap(), 0.125);
+    }
+
+    #[test]
+    fn test_mse_shape_mismatch() {
+        let a = Canvas::from_wh(2, 2);
+        let b = Canvas::from_wh(3, 2);
+        assert!(matches!(
+            Mse.distance(&a, &b),
+            Err(Error::ShapeMismatch { .. })
+        ));
+    }
+}
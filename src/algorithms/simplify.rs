@@ -0,0 +1,218 @@
+use num_traits::AsPrimitive;
+
+use crate::geometry::{Line, Point, Polygon};
+
+// All simplifications keep a subset of the original vertices in order, and may return
+// fewer than three when the polygon is thinner than the tolerance.
+
+fn to_f64<T: Copy + AsPrimitive<f64>>(p: &Point<T>) -> Point<f64> {
+    Point::new(p.x.as_(), p.y.as_())
+}
+
+fn keep<T: Copy>(poly: &Polygon<T>, kept: &[bool]) -> Polygon<T> {
+    Polygon {
+        vertices: poly
+            .vertices
+            .iter()
+            .zip(kept)
+            .filter(|(_, &k)| k)
+            .map(|(&p, _)| p)
+            .collect(),
+    }
+}
+
+/// Ramer–Douglas–Peucker: drops vertices closer than `tolerance` to the simplified
+/// outline. The closed ring is split at vertex 0 and the vertex farthest from it.
+pub fn rdp<T>(poly: &Polygon<T>, tolerance: f64) -> Polygon<T>
+where
+    T: Copy + AsPrimitive<f64>,
+{
+    let n = poly.vertices.len();
+    if n <= 3 {
+        return poly.clone();
+    }
+    let points: Vec<Point<f64>> = poly.vertices.iter().map(to_f64).collect();
+    let far = (1..n)
+        .max_by(|&a, &b| {
+            let d = |i: usize| {
+                let v = points[i] - points[0];
+                v.x * v.x + v.y * v.y
+            };
+            d(a).total_cmp(&d(b))
+        })
+        .unwrap_or(1);
+    let mut kept = vec![false; n];
+    kept[0] = true;
+    kept[far] = true;
+    // Indices past `n` wrap around to close the ring
+    let mut stack = vec![(0, far), (far, n)];
+    while let Some((start, end)) = stack.pop() {
+        let line = Line::new(points[start], points[end % n]);
+        let farthest = (start + 1..end)
+            .map(|i| (i, line.distance_to_point(points[i])))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((i, dist)) = farthest {
+            if dist > tolerance {
+                kept[i] = true;
+                stack.push((start, i));
+                stack.push((i, end));
+            }
+        }
+    }
+    keep(poly, &kept)
+}
+
+// Distance from `p` to the infinite line through `a` and `b`.
+fn line_distance(a: Point<f64>, b: Point<f64>, p: Point<f64>) -> f64 {
+    let d = b - a;
+    let len = d.x.hypot(d.y);
+    if len == 0.0 {
+        (p - a).x.hypot((p - a).y)
+    } else {
+        (d.x * (p.y - a.y) - d.y * (p.x - a.x)).abs() / len
+    }
+}
+
+// Area of the triangle a, b, c.
+fn triangle_area(a: Point<f64>, b: Point<f64>, c: Point<f64>) -> f64 {
+    ((b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)).abs() / 2.0
+}
+
+/// Visvalingam–Whyatt: repeatedly drops the vertex whose triangle with its neighbors
+/// has the smallest area, while that area is below `min_area` and more than three
+/// vertices remain.
+pub fn visvalingam<T>(poly: &Polygon<T>, min_area: f64) -> Polygon<T>
+where
+    T: Copy + AsPrimitive<f64>,
+{
+    let points: Vec<Point<f64>> = poly.vertices.iter().map(to_f64).collect();
+    let mut ring: Vec<usize> = (0..points.len()).collect();
+    while ring.len() > 3 {
+        let n = ring.len();
+        let area = |i: usize| {
+            triangle_area(
+                points[ring[(i + n - 1) % n]],
+                points[ring[i]],
+                points[ring[(i + 1) % n]],
+            )
+        };
+        let (i, smallest) = (0..n)
+            .map(|i| (i, area(i)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0, f64::INFINITY));
+        if smallest >= min_area {
+            break;
+        }
+        ring.remove(i);
+    }
+    let mut kept = vec![false; points.len()];
+    ring.into_iter().for_each(|i| kept[i] = true);
+    keep(poly, &kept)
+}
+
+/// Drops vertices within `tolerance` of the previous kept vertex, including across the
+/// closing edge.
+pub fn dedup<T>(poly: &Polygon<T>, tolerance: f64) -> Polygon<T>
+where
+    T: Copy + AsPrimitive<f64>,
+{
+    let mut vertices: Vec<Point<T>> = Vec::with_capacity(poly.vertices.len());
+    let close = |a: &Point<T>, b: &Point<T>| {
+        let d = to_f64(a) - to_f64(b);
+        d.x.hypot(d.y) <= tolerance
+    };
+    for p in &poly.vertices {
+        if vertices.last().is_none_or(|last| !close(last, p)) {
+            vertices.push(*p);
+        }
+    }
+    while vertices.len() > 1 && close(&vertices[0], &vertices[vertices.len() - 1]) {
+        vertices.pop();
+    }
+    Polygon { vertices }
+}
+
+/// Drops vertices within `tolerance` of the line through their neighbors, including
+/// spikes doubling back along it.
+pub fn remove_collinear<T>(poly: &Polygon<T>, tolerance: f64) -> Polygon<T>
+where
+    T: Copy + AsPrimitive<f64>,
+{
+    let points: Vec<Point<f64>> = poly.vertices.iter().map(to_f64).collect();
+    let mut ring: Vec<usize> = (0..points.len()).collect();
+    let mut i = 0;
+    let mut since_removal = 0;
+    // Stop after a full lap without removals
+    while ring.len() > 2 && since_removal < ring.len() {
+        let n = ring.len();
+        i %= n;
+        let (prev, curr, next) = (
+            points[ring[(i + n - 1) % n]],
+            points[ring[i]],
+            points[ring[(i + 1) % n]],
+        );
+        if line_distance(prev, next, curr) <= tolerance {
+            ring.remove(i);
+            since_removal = 0;
+        } else {
+            i += 1;
+            since_removal += 1;
+        }
+    }
+    let mut kept = vec![false; points.len()];
+    ring.into_iter().for_each(|i| kept[i] = true);
+    keep(poly, &kept)
+}
+
+#[cfg(test)]
+mod simplify_tests {
+    use super::*;
+
+    #[test]
+    fn test_rdp() {
+        // A square with slightly bumpy sides
+        let poly = Polygon::from_vec(vec![
+            0.0, 0.0, 5.0, 0.1, 10.0, 0.0, 9.9, 5.0, 10.0, 10.0, 5.0, 10.05, 0.0, 10.0, 0.1, 5.0,
+        ])
+        .unwrap();
+        let simple = rdp(&poly, 0.2);
+        assert_eq!(
+            simple,
+            Polygon::from_vec(vec![0.0, 0.0, 10.0, 0.0, 10.0, 10.0, 0.0, 10.0]).unwrap()
+        );
+        assert_eq!(rdp(&poly, 0.01), poly);
+    }
+
+    #[test]
+    fn test_visvalingam() {
+        let poly = Polygon::from_vec(vec![0, 0, 5, 1, 10, 0, 10, 10, 0, 10]).unwrap();
+        assert_eq!(visvalingam(&poly, 1.0), poly);
+        assert_eq!(
+            visvalingam(&poly, 10.0),
+            Polygon::from_vec(vec![0, 0, 10, 0, 10, 10, 0, 10]).unwrap()
+        );
+        // Never goes below a triangle
+        assert_eq!(visvalingam(&poly, f64::INFINITY).vertices.len(), 3);
+    }
+
+    #[test]
+    fn test_dedup() {
+        let poly = Polygon::from_vec(vec![
+            0.0, 0.0, 0.001, 0.0, 4.0, 0.0, 4.0, 4.0, 4.0, 4.0, 0.0, 4.0, 0.0, 0.0005,
+        ])
+        .unwrap();
+        assert_eq!(
+            dedup(&poly, 0.01),
+            Polygon::from_vec(vec![0.0, 0.0, 4.0, 0.0, 4.0, 4.0, 0.0, 4.0]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_remove_collinear() {
+        let poly = Polygon::from_vec(vec![0, 0, 2, 0, 4, 0, 4, 4, 4, 6, 4, 2, 0, 4]).unwrap();
+        assert_eq!(
+            remove_collinear(&poly, 0.0),
+            Polygon::from_vec(vec![0, 0, 4, 0, 4, 2, 0, 4]).unwrap()
+        );
+    }
+}
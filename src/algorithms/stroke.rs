@@ -0,0 +1,440 @@
+use palette::rgb::LinSrgba;
+
+use crate::algorithms::rasterizer::Rasterizer;
+use crate::canvas::{BlendMode, Canvas};
+use crate::error::Result;
+use crate::geometry::FillRule;
+use crate::geometry::{Circle, Line, Orientation, Point, Polygon, Primitive};
+
+// Largest distance between a round join or cap and its polygon approximation
+const ROUND_TOLERANCE: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    // Extends the outer edges until they meet, falling back to `Bevel` past the miter limit
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    // Extends the ends by half the stroke width
+    Square,
+}
+
+/// How a stroke is drawn. The defaults are those of SVG.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f64,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    // Longest miter allowed, as a multiple of `width`
+    pub miter_limit: f64,
+    // Alternating dash and gap lengths, repeated along the path. Empty for a solid stroke.
+    pub dashes: Vec<f64>,
+    pub dash_offset: f64,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        StrokeStyle {
+            width: 1.0,
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            miter_limit: 4.0,
+            dashes: Vec::new(),
+            dash_offset: 0.0,
+        }
+    }
+}
+
+impl StrokeStyle {
+    pub fn new(width: f64) -> Self {
+        StrokeStyle {
+            width,
+            ..Default::default()
+        }
+    }
+}
+
+// Unit normal to the left of a -> b, with the y axis pointing up.
+fn normal(a: Point<f64>, b: Point<f64>) -> Point<f64> {
+    let d = b - a;
+    let len = d.x.hypot(d.y);
+    Point::new(-d.y / len, d.x / len)
+}
+
+// Adds `poly` counter-clockwise, so that overlapping pieces never cancel under `NonZero`.
+fn push_ccw(out: &mut Vec<Polygon<f64>>, mut poly: Polygon<f64>) {
+    match poly.orientation() {
+        Orientation::Degenerate => {}
+        Orientation::Clockwise => {
+            poly.reverse();
+            out.push(poly);
+        }
+        Orientation::CounterClockwise => out.push(poly),
+    }
+}
+
+fn disc(center: Point<f64>, radius: f64) -> Polygon<f64> {
+    Circle::new(center, radius).flatten(ROUND_TOLERANCE)
+}
+
+fn segment(a: Point<f64>, b: Point<f64>, half_width: f64) -> Polygon<f64> {
+    let n = normal(a, b) * half_width;
+    Polygon {
+        vertices: vec![a - n, b - n, b + n, a + n],
+    }
+}
+
+// Fills the outer corner where the segments prev -> p and p -> next meet.
+fn join(
+    prev: Point<f64>,
+    p: Point<f64>,
+    next: Point<f64>,
+    style: &StrokeStyle,
+    out: &mut Vec<Polygon<f64>>,
+) {
+    let half_width = style.width / 2.0;
+    if style.join == LineJoin::Round {
+        push_ccw(out, disc(p, half_width));
+        return;
+    }
+    let (d0, d1) = (p - prev, next - p);
+    let cross = d0.x * d1.y - d0.y * d1.x;
+    if cross == 0.0 {
+        // Straight on, or doubling back where a miter would be infinitely long
+        return;
+    }
+    // The outer side is opposite to the turn
+    let side = if cross > 0.0 { -half_width } else { half_width };
+    let (n0, n1) = (normal(prev, p), normal(p, next));
+    let (a, b) = (p + n0 * side, p + n1 * side);
+    let sum = n0 + n1;
+    let len2 = sum.x * sum.x + sum.y * sum.y;
+    // The miter is 2 / |n0 + n1| times the stroke width
+    let vertices = if style.join == LineJoin::Miter
+        && len2 > 0.0
+        && 4.0 <= style.miter_limit * style.miter_limit * len2
+    {
+        vec![p, a, p + sum * (2.0 * side / len2), b]
+    } else {
+        vec![p, a, b]
+    };
+    push_ccw(out, Polygon { vertices });
+}
+
+fn stroke_open(points: &[Point<f64>], style: &StrokeStyle, out: &mut Vec<Polygon<f64>>) {
+    let half_width = style.width / 2.0;
+    let (first, last) = match points {
+        [] => return,
+        // A zero-length path still gets its caps, as in SVG
+        [p] => {
+            match style.cap {
+                LineCap::Butt => {}
+                LineCap::Round => push_ccw(out, disc(*p, half_width)),
+                LineCap::Square => {
+                    let (lo, hi) = (
+                        *p - Point::new(half_width, half_width),
+                        *p + Point::new(half_width, half_width),
+                    );
+                    let vertices = vec![lo, Point::new(hi.x, lo.y), hi, Point::new(lo.x, hi.y)];
+                    push_ccw(out, Polygon { vertices });
+                }
+            }
+            return;
+        }
+        [first, .., last] => (*first, *last),
+    };
+    let n = points.len();
+    let mut ends = [points[0], points[n - 1]];
+    if style.cap == LineCap::Square {
+        for (end, (from, to)) in ends
+            .iter_mut()
+            .zip([(points[1], first), (points[n - 2], last)])
+        {
+            let d = to - from;
+            *end = to + d * (half_width / d.x.hypot(d.y));
+        }
+    }
+    for i in 0..n - 1 {
+        let a = if i == 0 { ends[0] } else { points[i] };
+        let b = if i == n - 2 { ends[1] } else { points[i + 1] };
+        push_ccw(out, segment(a, b, half_width));
+    }
+    for w in points.windows(3) {
+        join(w[0], w[1], w[2], style, out);
+    }
+    if style.cap == LineCap::Round {
+        push_ccw(out, disc(first, half_width));
+        push_ccw(out, disc(last, half_width));
+    }
+}
+
+fn stroke_closed(points: &[Point<f64>], style: &StrokeStyle, out: &mut Vec<Polygon<f64>>) {
+    let n = points.len();
+    if n < 2 {
+        stroke_open(points, style, out);
+        return;
+    }
+    for i in 0..n {
+        let (prev, p, next) = (points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+        push_ccw(out, segment(p, next, style.width / 2.0));
+        join(prev, p, next, style, out);
+    }
+}
+
+// Splits the path into the dashes of `style`, or returns None for a solid stroke.
+// Negative or non-finite lengths also give a solid stroke, as in SVG.
+fn split_dashes(points: &[Point<f64>], style: &StrokeStyle) -> Option<Vec<Vec<Point<f64>>>> {
+    if style.dashes.iter().any(|d| !d.is_finite() || *d < 0.0) {
+        return None;
+    }
+    let mut pattern = style.dashes.clone();
+    if pattern.len() % 2 == 1 {
+        pattern.extend_from_within(..);
+    }
+    let total: f64 = pattern.iter().sum();
+    if total <= 0.0 || points.is_empty() {
+        return None;
+    }
+
+    // Find where in the pattern the path starts
+    let mut pos = style.dash_offset.rem_euclid(total);
+    let mut i = 0;
+    while i + 1 < pattern.len() && pos >= pattern[i] {
+        pos -= pattern[i];
+        i += 1;
+    }
+    let mut remaining = (pattern[i] - pos).max(0.0);
+    let mut on = i % 2 == 0;
+    let mut current = if on { vec![points[0]] } else { Vec::new() };
+    let mut dashes = Vec::new();
+    for w in points.windows(2) {
+        let (a, b) = (w[0], w[1]);
+        let len = (b - a).x.hypot((b - a).y);
+        let mut t = 0.0;
+        while len - t > remaining {
+            t += remaining;
+            let q = a + (b - a) * (t / len);
+            current.push(q);
+            if on {
+                dashes.push(std::mem::take(&mut current));
+            }
+            on = !on;
+            i = (i + 1) % pattern.len();
+            remaining = pattern[i];
+        }
+        remaining -= len - t;
+        if on {
+            current.push(b);
+        }
+    }
+    if on && current.len() > 1 {
+        dashes.push(current);
+    }
+    Some(dashes)
+}
+
+/// Converts a stroke along `points` into polygons whose union is the stroked area. The
+/// polygons are all counter-clockwise and overlap, so fill them together with `NonZero`,
+/// e.g. with `Rasterizer::fill_rings`. A `closed` path joins its last point to the first.
+pub fn stroke_path(points: &[Point<f64>], closed: bool, style: &StrokeStyle) -> Vec<Polygon<f64>> {
+    let mut points = points.to_vec();
+    points.dedup();
+    if closed && points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    let mut out = Vec::new();
+    if style.width.is_nan() || style.width <= 0.0 {
+        return out;
+    }
+    if closed && !points.is_empty() {
+        points.push(points[0]);
+    }
+    match split_dashes(&points, style) {
+        Some(dashes) => {
+            for mut dash in dashes {
+                dash.dedup();
+                stroke_open(&dash, style, &mut out);
+            }
+        }
+        None if closed => {
+            points.pop();
+            stroke_closed(&points, style, &mut out);
+        }
+        None => stroke_open(&points, style, &mut out),
+    }
+    out
+}
+
+pub fn stroke_line(line: &Line<f64>, style: &StrokeStyle) -> Vec<Polygon<f64>> {
+    stroke_path(&[line.start, line.end], false, style)
+}
+
+/// Strokes the outline of `poly`.
+pub fn stroke_polygon(poly: &Polygon<f64>, style: &StrokeStyle) -> Vec<Polygon<f64>> {
+    stroke_path(&poly.vertices, true, style)
+}
+
+/// Strokes the path on a `Canvas`, compositing `color` with `mode` once on every pixel
+/// the stroke covers.
+pub fn stroke_canvas(
+    canvas: &mut Canvas,
+    points: &[Point<f64>],
+    closed: bool,
+    style: &StrokeStyle,
+    color: LinSrgba<f64>,
+    mode: BlendMode,
+) -> Result<()> {
+    let pieces = stroke_path(points, closed, style);
+    Rasterizer::new().fill_rings(canvas, &pieces, color, FillRule::NonZero, mode)
+}
+
+#[cfg(test)]
+mod stroke_tests {
+    use super::*;
+    use crate::canvas::CanvasSpec;
+
+    fn covers(pieces: &[Polygon<f64>], x: f64, y: f64) -> bool {
+        pieces
+            .iter()
+            .any(|p| p.contains(Point::new(x, y), FillRule::NonZero))
+    }
+
+    fn painted(canvas: &Canvas) -> usize {
+        canvas.pixels().iter().filter(|p| p.red < 1.0).count()
+    }
+
+    #[test]
+    fn test_caps() {
+        let black = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        let line = [Point::new(2.0, 5.0), Point::new(8.0, 5.0)];
+        let mut style = StrokeStyle::new(2.0);
+        let mut canvas = Canvas::white(CanvasSpec::new(12, 12));
+        stroke_canvas(&mut canvas, &line, false, &style, black, BlendMode::Normal).unwrap();
+        assert_eq!(painted(&canvas), 6 * 2);
+
+        style.cap = LineCap::Square;
+        let mut canvas = Canvas::white(CanvasSpec::new(12, 12));
+        stroke_canvas(&mut canvas, &line, false, &style, black, BlendMode::Normal).unwrap();
+        assert_eq!(painted(&canvas), 8 * 2);
+
+        style.cap = LineCap::Round;
+        let pieces = stroke_line(&Line::new(line[0], line[1]), &style);
+        assert!(covers(&pieces, 1.2, 5.0));
+        assert!(!covers(&pieces, 1.2, 5.9));
+    }
+
+    #[test]
+    fn test_joins() {
+        let path = [
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 0.0),
+            Point::new(10.0, 10.0),
+        ];
+        let mut style = StrokeStyle::new(2.0);
+        let corner = |style: &StrokeStyle| covers(&stroke_path(&path, false, style), 10.8, -0.8);
+        assert!(corner(&style));
+        style.join = LineJoin::Bevel;
+        assert!(!corner(&style));
+        assert!(covers(&stroke_path(&path, false, &style), 10.4, -0.4));
+        style.join = LineJoin::Round;
+        assert!(!corner(&style));
+        assert!(covers(&stroke_path(&path, false, &style), 10.6, -0.6));
+        // A right angle needs a miter of sqrt(2) times the width
+        style.join = LineJoin::Miter;
+        style.miter_limit = 1.4;
+        assert!(!corner(&style));
+    }
+
+    #[test]
+    fn test_dashes() {
+        let path = [Point::new(0.0, 0.0), Point::new(10.0, 0.0)];
+        let mut style = StrokeStyle {
+            dashes: vec![2.0, 3.0],
+            ..Default::default()
+        };
+        let xs = |style: &StrokeStyle| -> Vec<(f64, f64)> {
+            split_dashes(&path, style)
+                .unwrap()
+                .iter()
+                .map(|d| (d[0].x, d[d.len() - 1].x))
+                .collect()
+        };
+        assert_eq!(xs(&style), vec![(0.0, 2.0), (5.0, 7.0)]);
+        style.dash_offset = 1.0;
+        assert_eq!(xs(&style), vec![(0.0, 1.0), (4.0, 6.0), (9.0, 10.0)]);
+        // An odd pattern repeats to alternate
+        style.dashes = vec![3.0];
+        style.dash_offset = 0.0;
+        assert_eq!(xs(&style), vec![(0.0, 3.0), (6.0, 9.0)]);
+        style.dashes = vec![-1.0, 2.0];
+        assert!(split_dashes(&path, &style).is_none());
+    }
+
+    #[test]
+    fn test_dashes_follow_corners() {
+        let path = [
+            Point::new(0.0, 0.0),
+            Point::new(4.0, 0.0),
+            Point::new(4.0, 4.0),
+        ];
+        let style = StrokeStyle {
+            dashes: vec![6.0, 1.0],
+            ..Default::default()
+        };
+        let dashes = split_dashes(&path, &style).unwrap();
+        assert_eq!(dashes[0], vec![path[0], path[1], Point::new(4.0, 2.0)]);
+        assert_eq!(dashes[1], vec![Point::new(4.0, 3.0), path[2]]);
+    }
+
+    #[test]
+    fn test_outline_composited_once() {
+        let half_black = LinSrgba::new(0.0, 0.0, 0.0, 0.5);
+        let square = Polygon::from_vec(vec![3.0, 3.0, 13.0, 3.0, 13.0, 13.0, 3.0, 13.0]).unwrap();
+        for join in [LineJoin::Miter, LineJoin::Round, LineJoin::Bevel] {
+            let style = StrokeStyle {
+                width: 3.0,
+                join,
+                ..Default::default()
+            };
+            let mut canvas = Canvas::white(CanvasSpec::new(16, 16));
+            stroke_canvas(
+                &mut canvas,
+                &square.vertices,
+                true,
+                &style,
+                half_black,
+                BlendMode::Normal,
+            )
+            .unwrap();
+            assert!(canvas.pixels().iter().all(|p| p.red == 1.0 || p.red == 0.5));
+            // The outline is hollow
+            assert_eq!(canvas.get_pixel(8, 8).unwrap().red, 1.0);
+            assert_eq!(canvas.get_pixel(8, 3).unwrap().red, 0.5);
+        }
+    }
+
+    #[test]
+    fn test_degenerate_paths() {
+        let style = StrokeStyle::new(2.0);
+        assert!(stroke_path(&[], false, &style).is_empty());
+        let dot = [Point::new(3.0, 3.0); 2];
+        assert!(stroke_path(&dot, false, &style).is_empty());
+        let round = StrokeStyle {
+            cap: LineCap::Round,
+            ..style.clone()
+        };
+        assert_eq!(stroke_path(&dot, false, &round).len(), 1);
+        let zero = StrokeStyle::new(0.0);
+        assert!(
+            stroke_path(&[Point::new(0.0, 0.0), Point::new(5.0, 5.0)], false, &zero).is_empty()
+        );
+    }
+}
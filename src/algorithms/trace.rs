@@ -0,0 +1,98 @@
+// Instrumentation hooks for the polygon fills. Fills are generic over the tracer, so
+// with `NoTrace` the calls compile away entirely.
+
+/// Receives events from a polygon fill. All methods do nothing by default.
+pub trait FillTracer {
+    /// Called once before each polygon is filled.
+    fn begin_polygon(&mut self) {}
+
+    /// Called for each interior span `x_start..x_end` on `row`, in row order.
+    fn span(&mut self, _row: usize, _x_start: usize, _x_end: usize) {}
+}
+
+/// The tracer used when none is requested.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoTrace;
+
+impl FillTracer for NoTrace {}
+
+/// What the fill of a single polygon did.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PolygonStats {
+    // Rows with at least one span
+    pub rows: usize,
+    pub spans: usize,
+    pub pixels: usize,
+}
+
+/// Counts what the fills did, in total over every polygon traced and for each polygon
+/// in `per_polygon`, in fill order.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FillStats {
+    pub polygons: usize,
+    // Rows with at least one span, counted once per polygon
+    pub rows: usize,
+    pub spans: usize,
+    pub pixels: usize,
+    pub per_polygon: Vec<PolygonStats>,
+    last_row: Option<usize>,
+}
+
+impl FillTracer for FillStats {
+    fn begin_polygon(&mut self) {
+        self.polygons += 1;
+        self.per_polygon.push(PolygonStats::default());
+        self.last_row = None;
+    }
+
+    fn span(&mut self, row: usize, x_start: usize, x_end: usize) {
+        if self.per_polygon.is_empty() {
+            self.per_polygon.push(PolygonStats::default());
+        }
+        let polygon = self.per_polygon.last_mut().expect("pushed above");
+        if self.last_row != Some(row) {
+            self.rows += 1;
+            polygon.rows += 1;
+            self.last_row = Some(row);
+        }
+        self.spans += 1;
+        polygon.spans += 1;
+        self.pixels += x_end - x_start;
+        polygon.pixels += x_end - x_start;
+    }
+}
+
+#[cfg(test)]
+mod trace_tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_stats() {
+        let mut stats = FillStats::default();
+        stats.begin_polygon();
+        stats.span(2, 0, 4);
+        stats.span(2, 6, 7);
+        stats.span(3, 1, 3);
+        stats.begin_polygon();
+        stats.span(3, 0, 1);
+        assert_eq!(
+            (stats.polygons, stats.rows, stats.spans, stats.pixels),
+            (2, 3, 4, 8)
+        );
+        assert_eq!(
+            stats.per_polygon,
+            vec![
+                PolygonStats {
+                    rows: 2,
+                    spans: 3,
+                    pixels: 7
+                },
+                PolygonStats {
+                    rows: 1,
+                    spans: 1,
+                    pixels: 1
+                },
+            ]
+        );
+    }
+}
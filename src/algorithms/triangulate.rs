@@ -0,0 +1,414 @@
+use std::collections::{HashMap, HashSet};
+
+use num_traits::AsPrimitive;
+
+use crate::algorithms::intersections::segments_touch;
+use crate::geometry::{Point, Polygon};
+
+// Vertex indices of a triangle. Triangles produced here are counter-clockwise
+// (see `Orientation`).
+pub type Triangle = [usize; 3];
+
+fn cross(o: Point<f64>, a: Point<f64>, b: Point<f64>) -> f64 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+// All vertices of the exterior followed by those of each hole, and the index range of
+// every ring in that list.
+fn collect_rings<T>(
+    exterior: &Polygon<T>,
+    holes: &[Polygon<T>],
+) -> (Vec<Point<f64>>, Vec<Vec<usize>>)
+where
+    T: Copy + AsPrimitive<f64>,
+{
+    let mut points = Vec::new();
+    let mut rings = Vec::new();
+    for ring in std::iter::once(exterior).chain(holes) {
+        let start = points.len();
+        points.extend(
+            ring.vertices
+                .iter()
+                .map(|p| Point::new(p.x.as_(), p.y.as_())),
+        );
+        rings.push((start..points.len()).collect());
+    }
+    (points, rings)
+}
+
+fn ring_area(points: &[Point<f64>], ring: &[usize]) -> f64 {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (points[ring[i]], points[ring[(i + 1) % n]]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+/// Ear clipping of a simple polygon. Indices refer to `poly.vertices`.
+pub fn ear_clip<T>(poly: &Polygon<T>) -> Vec<Triangle>
+where
+    T: Copy + AsPrimitive<f64>,
+{
+    ear_clip_with_holes(poly, &[])
+}
+
+/// Ear clipping of a simple polygon with simple holes inside it. Indices refer to the
+/// exterior vertices followed by the vertices of each hole in order.
+///
+/// Every hole is first joined to the outer boundary through its nearest visible
+/// vertex, giving a single ring that touches itself along the bridges. An exterior with
+/// fewer than 3 vertices gives no triangles, and holes with fewer than 3 are ignored.
+pub fn ear_clip_with_holes<T>(exterior: &Polygon<T>, holes: &[Polygon<T>]) -> Vec<Triangle>
+where
+    T: Copy + AsPrimitive<f64>,
+{
+    if exterior.vertices.len() < 3 {
+        return Vec::new();
+    }
+    let (points, mut rings) = collect_rings(exterior, holes);
+    // Exterior counter-clockwise, holes clockwise
+    for (i, ring) in rings.iter_mut().enumerate() {
+        if (ring_area(&points, ring) > 0.0) != (i == 0) {
+            ring.reverse();
+        }
+    }
+    let mut holes: Vec<Vec<usize>> = rings.split_off(1);
+    holes.retain(|hole| hole.len() >= 3);
+    let mut outer = rings.pop().unwrap_or_default();
+    // Rightmost holes first, so earlier bridges rarely block later ones
+    let max_x = |ring: &Vec<usize>| ring.iter().map(|&i| points[i].x).fold(f64::MIN, f64::max);
+    holes.sort_by(|a, b| max_x(b).total_cmp(&max_x(a)));
+    while !holes.is_empty() {
+        let hole = holes.remove(0);
+        outer = bridge(&points, outer, &hole, &holes);
+    }
+    clip_ears(&points, outer)
+}
+
+// Whether the direction `d` from ring position `k` points into the polygon.
+fn in_wedge(points: &[Point<f64>], ring: &[usize], k: usize, d: Point<f64>) -> bool {
+    let n = ring.len();
+    let v = points[ring[k]];
+    let to_prev = points[ring[(k + n - 1) % n]] - v;
+    let to_next = points[ring[(k + 1) % n]] - v;
+    let cross2 = |a: Point<f64>, b: Point<f64>| a.x * b.y - a.y * b.x;
+    if cross2(to_next, to_prev) > 0.0 {
+        // Convex: between the outgoing and incoming edges
+        cross2(to_next, d) > 0.0 && cross2(d, to_prev) > 0.0
+    } else {
+        // Reflex: anywhere but the outside wedge
+        !(cross2(to_prev, d) >= 0.0 && cross2(d, to_next) >= 0.0)
+    }
+}
+
+// Splices `hole` into `outer` through a segment that crosses no other edge.
+fn bridge(
+    points: &[Point<f64>],
+    outer: Vec<usize>,
+    hole: &[usize],
+    others: &[Vec<usize>],
+) -> Vec<usize> {
+    let m_pos = (0..hole.len())
+        .max_by(|&a, &b| points[hole[a]].x.total_cmp(&points[hole[b]].x))
+        .unwrap_or(0);
+    let m = points[hole[m_pos]];
+    let mut candidates: Vec<usize> = (0..outer.len()).collect();
+    let dist = |k: usize| {
+        let d = points[outer[k]] - m;
+        d.x * d.x + d.y * d.y
+    };
+    candidates.sort_by(|&a, &b| dist(a).total_cmp(&dist(b)));
+
+    let blocked = |v: Point<f64>| {
+        let ring_edges = |ring: &[usize]| {
+            let n = ring.len();
+            (0..n)
+                .map(move |i| (points[ring[i]], points[ring[(i + 1) % n]]))
+                .collect::<Vec<_>>()
+        };
+        std::iter::once(&outer[..])
+            .chain(std::iter::once(hole))
+            .chain(others.iter().map(|r| &r[..]))
+            .flat_map(ring_edges)
+            .filter(|&(a, b)| a != v && b != v && a != m && b != m)
+            .any(|(a, b)| segments_touch(a, b, v, m))
+    };
+    let k = candidates
+        .iter()
+        .copied()
+        .find(|&k| {
+            let v = points[outer[k]];
+            v != m && in_wedge(points, &outer, k, m - v) && !blocked(v)
+        })
+        .unwrap_or(candidates[0]);
+
+    let mut merged = Vec::with_capacity(outer.len() + hole.len() + 2);
+    merged.extend_from_slice(&outer[..=k]);
+    merged.extend(hole[m_pos..].iter().chain(&hole[..=m_pos]));
+    merged.extend_from_slice(&outer[k..]);
+    merged
+}
+
+fn in_triangle(a: Point<f64>, b: Point<f64>, c: Point<f64>, p: Point<f64>) -> bool {
+    cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0
+}
+
+fn clip_ears(points: &[Point<f64>], mut ring: Vec<usize>) -> Vec<Triangle> {
+    let mut triangles = Vec::with_capacity(ring.len().saturating_sub(2));
+    while ring.len() >= 3 {
+        let n = ring.len();
+        let corners = |i: usize| (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+        let turn = |i: usize| {
+            let (a, b, c) = corners(i);
+            cross(points[a], points[b], points[c])
+        };
+        let is_ear = |i: usize| {
+            let (a, b, c) = corners(i);
+            let (pa, pb, pc) = (points[a], points[b], points[c]);
+            turn(i) > 0.0
+                && !ring.iter().any(|&j| {
+                    let p = points[j];
+                    p != pa && p != pb && p != pc && in_triangle(pa, pb, pc, p)
+                })
+        };
+        // Collinear vertices are dropped without a triangle
+        if let Some(i) = (0..n).find(|&i| turn(i) == 0.0) {
+            ring.remove(i);
+            continue;
+        }
+        // Fall back to the most convex vertex if rounding left no clean ear
+        let i = (0..n).find(|&i| is_ear(i)).unwrap_or_else(|| {
+            (0..n)
+                .max_by(|&x, &y| turn(x).total_cmp(&turn(y)))
+                .unwrap_or(0)
+        });
+        if turn(i) > 0.0 {
+            let (a, b, c) = corners(i);
+            triangles.push([a, b, c]);
+        }
+        ring.remove(i);
+    }
+    triangles
+}
+
+/// Constrained Delaunay triangulation of a polygon with holes: the ear clipping
+/// result with interior edges flipped until no triangle's circumcircle holds the
+/// opposite vertex of a neighbor. Polygon edges are never flipped.
+pub fn delaunay<T>(exterior: &Polygon<T>, holes: &[Polygon<T>]) -> Vec<Triangle>
+where
+    T: Copy + AsPrimitive<f64>,
+{
+    let mut triangles = ear_clip_with_holes(exterior, holes);
+    let (points, rings) = collect_rings(exterior, holes);
+    let constraints: HashSet<(usize, usize)> = rings
+        .iter()
+        .flat_map(|ring| {
+            let n = ring.len();
+            (0..n).map(move |i| edge_key(ring[i], ring[(i + 1) % n]))
+        })
+        .collect();
+    flip_to_delaunay(&points, &mut triangles, &constraints);
+    triangles
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+// Whether `d` lies strictly inside the circumcircle of the counter-clockwise triangle
+// `a, b, c`, ignoring differences within rounding error.
+fn in_circumcircle(a: Point<f64>, b: Point<f64>, c: Point<f64>, d: Point<f64>) -> bool {
+    let (ad, bd, cd) = (a - d, b - d, c - d);
+    let lift = |p: Point<f64>| p.x * p.x + p.y * p.y;
+    let terms = [
+        lift(ad) * (bd.x * cd.y - cd.x * bd.y),
+        lift(bd) * (cd.x * ad.y - ad.x * cd.y),
+        lift(cd) * (ad.x * bd.y - bd.x * ad.y),
+    ];
+    let bound = lift(ad) * (bd.x * cd.y).abs().max((cd.x * bd.y).abs())
+        + lift(bd) * (cd.x * ad.y).abs().max((ad.x * cd.y).abs())
+        + lift(cd) * (ad.x * bd.y).abs().max((bd.x * ad.y).abs());
+    terms.iter().sum::<f64>() > 1e-12 * bound
+}
+
+// Lawson's flip algorithm.
+fn flip_to_delaunay(
+    points: &[Point<f64>],
+    triangles: &mut [Triangle],
+    constraints: &HashSet<(usize, usize)>,
+) {
+    let mut edges: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (t, tri) in triangles.iter().enumerate() {
+        for i in 0..3 {
+            edges
+                .entry(edge_key(tri[i], tri[(i + 1) % 3]))
+                .or_default()
+                .push(t);
+        }
+    }
+    let mut stack: Vec<(usize, usize)> = edges
+        .keys()
+        .copied()
+        .filter(|e| !constraints.contains(e))
+        .collect();
+    stack.sort_unstable();
+
+    while let Some(edge) = stack.pop() {
+        let Some(&[t1, t2]) = edges.get(&edge).map(|v| &v[..]) else {
+            continue;
+        };
+        // t1 = (a, b, c) and t2 = (b, a, d)
+        let opposite = |tri: Triangle| tri.iter().position(|&v| v != edge.0 && v != edge.1);
+        let (Some(i1), Some(i2)) = (opposite(triangles[t1]), opposite(triangles[t2])) else {
+            continue;
+        };
+        let tri1 = triangles[t1];
+        let (a, b, c) = (tri1[(i1 + 1) % 3], tri1[(i1 + 2) % 3], tri1[i1]);
+        let d = triangles[t2][i2];
+        let (pa, pb, pc, pd) = (points[a], points[b], points[c], points[d]);
+        if !in_circumcircle(pa, pb, pc, pd) || cross(pa, pd, pc) <= 0.0 || cross(pd, pb, pc) <= 0.0
+        {
+            continue;
+        }
+
+        for (t, tri) in [(t1, triangles[t1]), (t2, triangles[t2])] {
+            for i in 0..3 {
+                if let Some(owners) = edges.get_mut(&edge_key(tri[i], tri[(i + 1) % 3])) {
+                    owners.retain(|&o| o != t);
+                }
+            }
+        }
+        edges.remove(&edge);
+        triangles[t1] = [a, d, c];
+        triangles[t2] = [d, b, c];
+        for t in [t1, t2] {
+            let tri = triangles[t];
+            for i in 0..3 {
+                edges
+                    .entry(edge_key(tri[i], tri[(i + 1) % 3]))
+                    .or_default()
+                    .push(t);
+            }
+        }
+        for e in [(a, d), (d, b), (b, c), (c, a)] {
+            let e = edge_key(e.0, e.1);
+            if !constraints.contains(&e) {
+                stack.push(e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod triangulate_tests {
+    use super::*;
+    use crate::geometry::FillRule;
+    use crate::test_utils::square;
+    use approx::assert_relative_eq;
+
+    fn triangle_area(points: &[Point<f64>], t: &Triangle) -> f64 {
+        cross(points[t[0]], points[t[1]], points[t[2]]) / 2.0
+    }
+
+    fn check_cover(exterior: &Polygon<f64>, holes: &[Polygon<f64>], triangles: &[Triangle]) {
+        let (points, _) = collect_rings(exterior, holes);
+        let total: f64 = triangles.iter().map(|t| triangle_area(&points, t)).sum();
+        let expected = exterior.area() - holes.iter().map(|h| h.area()).sum::<f64>();
+        assert_relative_eq!(total, expected, epsilon = 1e-9);
+        for t in triangles {
+            assert!(triangle_area(&points, t) > 0.0, "{:?}", t);
+        }
+    }
+
+    #[test]
+    fn test_ear_clip_simple() {
+        let sq = square(0.0, 0.0, 4.0);
+        let triangles = ear_clip(&sq);
+        assert_eq!(triangles.len(), 2);
+        check_cover(&sq, &[], &triangles);
+        // Clockwise input gives the same counter-clockwise triangles
+        let mut cw = sq.clone();
+        cw.reverse();
+        check_cover(&cw, &[], &ear_clip(&cw));
+    }
+
+    #[test]
+    fn test_ear_clip_concave() {
+        let arrow = Polygon::from_vec(vec![0.0, 0.0, 6.0, 3.0, 0.0, 6.0, 2.0, 3.0]).unwrap();
+        check_cover(&arrow, &[], &ear_clip(&arrow));
+        let comb = Polygon::from_vec(vec![
+            0.0, 0.0, 5.0, 0.0, 5.0, 4.0, 4.0, 4.0, 4.0, 1.0, 3.0, 1.0, 3.0, 4.0, 2.0, 4.0, 2.0,
+            1.0, 1.0, 1.0, 1.0, 4.0, 0.0, 4.0,
+        ])
+        .unwrap();
+        check_cover(&comb, &[], &ear_clip(&comb));
+    }
+
+    #[test]
+    fn test_ear_clip_with_holes() {
+        let outer = square(0.0, 0.0, 10.0);
+        let holes = [square(2.0, 2.0, 2.0), square(6.0, 5.0, 2.0)];
+        let triangles = ear_clip_with_holes(&outer, &holes);
+        check_cover(&outer, &holes, &triangles);
+        let (points, _) = collect_rings(&outer, &holes);
+        for t in &triangles {
+            let c = Point::new(
+                (points[t[0]].x + points[t[1]].x + points[t[2]].x) / 3.0,
+                (points[t[0]].y + points[t[1]].y + points[t[2]].y) / 3.0,
+            );
+            assert!(holes.iter().all(|h| !h.contains(c, FillRule::NonZero)));
+        }
+    }
+
+    #[test]
+    fn test_degenerate_exterior_with_holes() {
+        let holes = [square(2.0, 2.0, 2.0)];
+        assert!(ear_clip_with_holes(&Polygon::<f64>::new(), &holes).is_empty());
+        let mut segment = Polygon::new();
+        segment.add_point(Point::new(0.0, 0.0));
+        segment.add_point(Point::new(10.0, 0.0));
+        assert!(ear_clip_with_holes(&segment, &holes).is_empty());
+        assert!(delaunay(&Polygon::<f64>::new(), &holes).is_empty());
+    }
+
+    #[test]
+    fn test_degenerate_holes_are_skipped() {
+        let outer = square(0.0, 0.0, 10.0);
+        let mut segment = Polygon::new();
+        segment.add_point(Point::new(6.0, 6.0));
+        segment.add_point(Point::new(7.0, 7.0));
+        let holes = [Polygon::new(), square(2.0, 2.0, 2.0), segment];
+        let triangles = ear_clip_with_holes(&outer, &holes);
+        check_cover(&outer, &holes[1..2], &triangles);
+        let triangles = delaunay(&outer, &holes);
+        check_cover(&outer, &holes[1..2], &triangles);
+    }
+
+    #[test]
+    fn test_delaunay_is_locally_delaunay() {
+        // Points on a circle-ish convex outline, where ear clipping makes a fan of slivers
+        let outline = Polygon::from_vec(vec![
+            0.0, 0.0, 4.0, -1.0, 8.0, 0.0, 9.0, 3.0, 8.0, 6.0, 4.0, 7.0, 0.0, 6.0, -1.0, 3.0,
+        ])
+        .unwrap();
+        let holes = [square(3.0, 2.0, 2.0)];
+        let triangles = delaunay(&outline, &holes);
+        check_cover(&outline, &holes, &triangles);
+        let (points, _) = collect_rings(&outline, &holes);
+        for t in &triangles {
+            let (a, b, c) = (points[t[0]], points[t[1]], points[t[2]]);
+            for other in &triangles {
+                let shared = other.iter().filter(|v| t.contains(v)).count();
+                if shared != 2 {
+                    continue;
+                }
+                // Polygon edges belong to a single triangle, so this edge is interior
+                let d = other.iter().find(|v| !t.contains(v)).unwrap();
+                assert!(!in_circumcircle(a, b, c, points[*d]));
+            }
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+
+use palette::{
+    rgb::{LinSrgb, LinSrgba},
+    Mix,
+};
+
+use super::{Canvas, CanvasSpec, Pixel};
+use crate::geometry::Point;
+
+// Number of levels per RGB channel when bucketing colors for `dominant_color`.
+const DOMINANT_LEVELS: f32 = 8.0;
+
+impl Canvas {
+    pub fn from_color(spec: CanvasSpec, color: LinSrgba<f32>) -> Self {
+        let mut canvas = Canvas {
+            buff: vec![0f32; spec.width * spec.height * 4],
+            spec,
+        };
+        canvas.pixels_mut().fill(color.into());
+        canvas
+    }
+
+    /// A fully white, opaque canvas.
+    pub fn white(spec: CanvasSpec) -> Self {
+        Self::from_color(spec, LinSrgba::new(1.0, 1.0, 1.0, 1.0))
+    }
+
+    /// Blends from `start_color` at `start` to `end_color` at `end`, constant along
+    /// lines perpendicular to `start -> end` and clamped beyond the endpoints.
+    pub fn from_linear_gradient(
+        spec: CanvasSpec,
+        start: Point<f64>,
+        end: Point<f64>,
+        start_color: LinSrgba<f32>,
+        end_color: LinSrgba<f32>,
+    ) -> Self {
+        let (dx, dy) = (end.x - start.x, end.y - start.y);
+        let len_sq = dx * dx + dy * dy;
+        Self::from_fn(spec, |x, y| {
+            let t = if len_sq > 0.0 {
+                ((x - start.x) * dx + (y - start.y) * dy) / len_sq
+            } else {
+                0.0
+            };
+            start_color.mix(end_color, t.clamp(0.0, 1.0) as f32)
+        })
+    }
+
+    /// Blends from `inner_color` at `center` to `outer_color` at `radius` and beyond.
+    pub fn from_radial_gradient(
+        spec: CanvasSpec,
+        center: Point<f64>,
+        radius: f64,
+        inner_color: LinSrgba<f32>,
+        outer_color: LinSrgba<f32>,
+    ) -> Self {
+        Self::from_fn(spec, |x, y| {
+            let dist = (x - center.x).hypot(y - center.y);
+            let t = if radius > 0.0 { dist / radius } else { 1.0 };
+            inner_color.mix(outer_color, t.clamp(0.0, 1.0) as f32)
+        })
+    }
+
+    /// A canvas filled with the average color of `reference`.
+    pub fn from_mean_of(spec: CanvasSpec, reference: &Canvas) -> Self {
+        Self::from_color(spec, reference.mean_color())
+    }
+
+    /// A canvas filled with the most common color of `reference`.
+    pub fn from_dominant_of(spec: CanvasSpec, reference: &Canvas) -> Self {
+        Self::from_color(spec, reference.dominant_color())
+    }
+
+    // Pixels are sampled at integer coordinates, the same positions `fill_polygon` uses.
+    fn from_fn(spec: CanvasSpec, f: impl Fn(f64, f64) -> LinSrgba<f32>) -> Self {
+        let mut canvas = Self::from_color(spec, LinSrgba::new(0.0, 0.0, 0.0, 0.0));
+        for (y, row) in canvas.rows_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = f(x as f64, y as f64).into();
+            }
+        }
+        canvas
+    }
+
+    /// Average over all pixels in linear light, weighting colors by their alpha. An empty
+    /// canvas yields transparent black.
+    pub fn mean_color(&self) -> LinSrgba<f32> {
+        mean_of(self.pixels().iter())
+    }
+
+    /// Buckets pixels into a coarse RGB histogram and returns the mean of the
+    /// fullest bucket. An empty canvas yields transparent black.
+    pub fn dominant_color(&self) -> LinSrgba<f32> {
+        let mut buckets: HashMap<[u8; 3], Mean> = HashMap::new();
+        for pixel in self.pixels() {
+            let color = LinSrgba::from(*pixel);
+            let key = [color.red, color.green, color.blue]
+                .map(|c| (c.clamp(0.0, 1.0) * (DOMINANT_LEVELS - 1.0)).round() as u8);
+            buckets.entry(key).or_default().add(pixel);
+        }
+        // Ties are broken by the bucket key so the result does not depend on hash order
+        buckets
+            .into_iter()
+            .max_by_key(|(key, bucket)| (bucket.count, *key))
+            .map_or(LinSrgba::new(0.0, 0.0, 0.0, 0.0), |(_, bucket)| {
+                bucket.color()
+            })
+    }
+}
+
+fn mean_of<'a>(pixels: impl Iterator<Item = &'a Pixel>) -> LinSrgba<f32> {
+    let mut mean = Mean::default();
+    pixels.for_each(|pixel| mean.add(pixel));
+    mean.color()
+}
+
+// Running sum of premultiplied pixels, in f64 so large canvases do not lose precision.
+#[derive(Default)]
+struct Mean {
+    sum: [f64; 4],
+    count: usize,
+}
+
+impl Mean {
+    fn add(&mut self, pixel: &Pixel) {
+        self.sum[0] += pixel.red as f64;
+        self.sum[1] += pixel.green as f64;
+        self.sum[2] += pixel.blue as f64;
+        self.sum[3] += pixel.alpha as f64;
+        self.count += 1;
+    }
+
+    // Transparent black when nothing was added.
+    fn color(&self) -> LinSrgba<f32> {
+        if self.count == 0 {
+            return LinSrgba::new(0.0, 0.0, 0.0, 0.0);
+        }
+        let [r, g, b, a] = self.sum.map(|c| (c / self.count as f64) as f32);
+        Pixel {
+            color: LinSrgb::new(r, g, b),
+            alpha: a,
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod background_tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_solid_backgrounds() {
+        let blank = Canvas::from_wh(3, 2);
+        assert!(blank
+            .pixels()
+            .iter()
+            .all(|&p| LinSrgba::from(p) == LinSrgba::new(0.0, 0.0, 0.0, 0.0)));
+        let canvas = Canvas::white(CanvasSpec::new(3, 2));
+        let white = LinSrgba::new(1.0, 1.0, 1.0, 1.0);
+        assert!(canvas.pixels().iter().all(|&p| LinSrgba::from(p) == white));
+    }
+
+    #[test]
+    fn test_linear_gradient() {
+        let black = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        let white = LinSrgba::new(1.0, 1.0, 1.0, 1.0);
+        let canvas = Canvas::from_linear_gradient(
+            CanvasSpec::new(11, 3),
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 0.0),
+            black,
+            white,
+        );
+        assert_relative_eq!(canvas.get_pixel(0, 2).unwrap(), black);
+        assert_relative_eq!(canvas.get_pixel(5, 1).unwrap().red, 0.5);
+        assert_relative_eq!(canvas.get_pixel(10, 0).unwrap(), white);
+    }
+
+    #[test]
+    fn test_radial_gradient_clamps_outside() {
+        let red = LinSrgba::new(1.0, 0.0, 0.0, 1.0);
+        let blue = LinSrgba::new(0.0, 0.0, 1.0, 1.0);
+        let canvas = Canvas::from_radial_gradient(
+            CanvasSpec::new(9, 9),
+            Point::new(4.0, 4.0),
+            2.0,
+            red,
+            blue,
+        );
+        assert_relative_eq!(canvas.get_pixel(4, 4).unwrap(), red);
+        assert_relative_eq!(canvas.get_pixel(5, 4).unwrap().red, 0.5);
+        assert_relative_eq!(canvas.get_pixel(0, 0).unwrap(), blue);
+    }
+
+    #[test]
+    fn test_mean_and_dominant_color() {
+        let mut reference =
+            Canvas::from_color(CanvasSpec::new(4, 1), LinSrgba::new(0.0, 0.0, 0.0, 1.0));
+        reference
+            .put_pixel(0, 0, LinSrgba::new(1.0, 1.0, 1.0, 1.0))
+            .unwrap();
+        assert_relative_eq!(reference.mean_color(), LinSrgba::new(0.25, 0.25, 0.25, 1.0));
+        assert_relative_eq!(
+            reference.dominant_color(),
+            LinSrgba::new(0.0, 0.0, 0.0, 1.0)
+        );
+
+        let canvas = Canvas::from_mean_of(CanvasSpec::new(2, 2), &reference);
+        assert_eq!(canvas.get_spec(), CanvasSpec::new(2, 2));
+        assert_relative_eq!(canvas.get_pixel(1, 1).unwrap().green, 0.25);
+    }
+
+    #[test]
+    fn test_empty_canvas_colors() {
+        let canvas = Canvas::from_wh(0, 0);
+        assert_eq!(canvas.mean_color(), LinSrgba::new(0.0, 0.0, 0.0, 0.0));
+        assert_eq!(canvas.dominant_color(), LinSrgba::new(0.0, 0.0, 0.0, 0.0));
+    }
+}
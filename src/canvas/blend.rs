@@ -0,0 +1,228 @@
+use palette::{
+    blend::{Blend, Compose},
+    cast,
+    rgb::LinSrgba,
+};
+
+use super::Pixel;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    // Source-over alpha compositing
+    #[default]
+    Normal,
+    Multiply,
+}
+
+impl BlendMode {
+    /// Composites `fg` onto `bg`, both with straight alpha.
+    pub fn blend(&self, bg: LinSrgba<f32>, fg: LinSrgba<f32>) -> LinSrgba<f32> {
+        // palette premultiplies both colors before composing
+        match self {
+            Self::Normal => fg.over(bg),
+            Self::Multiply => bg.multiply(fg),
+        }
+    }
+
+    /// Composites the straight-alpha `fg` onto every premultiplied pixel of `span`.
+    /// Uses AVX when the CPU has it; both paths give the same result, which matches
+    /// `blend` up to rounding.
+    pub fn blend_span(&self, span: &mut [Pixel], fg: LinSrgba<f32>) {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx") {
+            let span: &mut [f32] = cast::into_component_slice_mut(span);
+            // SAFETY: AVX support was just checked
+            unsafe { avx::blend_span(*self, span, premultiply(fg)) };
+            return;
+        }
+        blend_span_scalar(*self, span, fg);
+    }
+
+    /// Composites a straight-alpha color onto each pixel of `span`, taken in order
+    /// from `colors`.
+    pub fn blend_each(&self, span: &mut [Pixel], colors: impl IntoIterator<Item = LinSrgba<f32>>) {
+        let span: &mut [f32] = cast::into_component_slice_mut(span);
+        for (pixel, fg) in span.chunks_exact_mut(4).zip(colors) {
+            blend_pixel(*self, pixel, premultiply(fg));
+        }
+    }
+}
+
+fn premultiply(color: LinSrgba<f32>) -> [f32; 4] {
+    cast::into_array(Pixel::from(color))
+}
+
+fn blend_span_scalar(mode: BlendMode, span: &mut [Pixel], fg: LinSrgba<f32>) {
+    let fg = premultiply(fg);
+    let span: &mut [f32] = cast::into_component_slice_mut(span);
+    for pixel in span.chunks_exact_mut(4) {
+        blend_pixel(mode, pixel, fg);
+    }
+}
+
+// The Porter-Duff equations palette uses in `blend`. On premultiplied colors each
+// channel, alpha included, is one multiply-add:
+//   Normal:   f + (1 - fa) * b
+//   Multiply: b * (f + 1 - fa) + f * (1 - ba)
+// The SIMD path performs the same operations in the same order, so the two agree bit
+// for bit.
+fn blend_pixel(mode: BlendMode, pixel: &mut [f32], fg: [f32; 4]) {
+    let (inv_fa, inv_ba) = (1.0 - fg[3], 1.0 - pixel[3]);
+    for (b, f) in pixel.iter_mut().zip(fg) {
+        *b = match mode {
+            BlendMode::Normal => f + inv_fa * *b,
+            BlendMode::Multiply => *b * (f + inv_fa) + f * inv_ba,
+        };
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx {
+    use std::arch::x86_64::*;
+
+    use super::BlendMode;
+
+    // Blends two pixels per 256-bit register, leaving any odd pixel to `blend_pixel`.
+    #[target_feature(enable = "avx")]
+    pub(super) unsafe fn blend_span(mode: BlendMode, span: &mut [f32], fg: [f32; 4]) {
+        let one = _mm256_set1_ps(1.0);
+        let [r, g, b, a] = fg;
+        let f = _mm256_setr_ps(r, g, b, a, r, g, b, a);
+        let inv_fa = _mm256_set1_ps(1.0 - a);
+        let f_plus_inv_fa = _mm256_add_ps(f, inv_fa);
+        let mut chunks = span.chunks_exact_mut(8);
+        for chunk in &mut chunks {
+            // SAFETY: the chunk holds exactly 8 floats
+            let c = unsafe { _mm256_loadu_ps(chunk.as_ptr()) };
+            let out = match mode {
+                BlendMode::Normal => _mm256_add_ps(f, _mm256_mul_ps(inv_fa, c)),
+                BlendMode::Multiply => {
+                    // Each pixel's alpha in all four of its lanes
+                    let ba = _mm256_permute_ps(c, 0b11_11_11_11);
+                    _mm256_add_ps(
+                        _mm256_mul_ps(c, f_plus_inv_fa),
+                        _mm256_mul_ps(f, _mm256_sub_ps(one, ba)),
+                    )
+                }
+            };
+            // SAFETY: as for the load
+            unsafe { _mm256_storeu_ps(chunk.as_mut_ptr(), out) };
+        }
+        for pixel in chunks.into_remainder().chunks_exact_mut(4) {
+            super::blend_pixel(mode, pixel, fg);
+        }
+    }
+}
+
+#[cfg(test)]
+mod blend_tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_normal_opaque_replaces() {
+        let bg = LinSrgba::new(0.2, 0.4, 0.6, 1.0);
+        let fg = LinSrgba::new(1.0, 0.0, 0.5, 1.0);
+        assert_relative_eq!(BlendMode::Normal.blend(bg, fg), fg);
+    }
+
+    #[test]
+    fn test_normal_half_transparent() {
+        let bg = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        let fg = LinSrgba::new(1.0, 1.0, 1.0, 0.5);
+        assert_relative_eq!(
+            BlendMode::Normal.blend(bg, fg),
+            LinSrgba::new(0.5, 0.5, 0.5, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_multiply_opaque() {
+        let bg = LinSrgba::new(0.5, 1.0, 0.2, 1.0);
+        let fg = LinSrgba::new(0.5, 0.5, 1.0, 1.0);
+        assert_relative_eq!(
+            BlendMode::Multiply.blend(bg, fg),
+            LinSrgba::new(0.25, 0.5, 0.2, 1.0)
+        );
+    }
+
+    fn random_pixels(rng: &mut crate::utils::Rng, n: usize) -> Vec<LinSrgba<f32>> {
+        (0..n)
+            .map(|i| {
+                let mut c = || rng.range_f64(0.0, 1.0) as f32;
+                // Include fully transparent and opaque pixels
+                let alpha = match i % 5 {
+                    0 => 0.0,
+                    1 => 1.0,
+                    _ => c(),
+                };
+                LinSrgba::new(c(), c(), c(), alpha)
+            })
+            .collect()
+    }
+
+    // Checks a span blend against `blend`, comparing premultiplied colors since
+    // straight colors are unstable near zero alpha.
+    fn check_matches_blend(seed: u64, blend_span: impl Fn(BlendMode, &mut [Pixel], LinSrgba<f32>)) {
+        let mut rng = crate::utils::Rng::new(seed);
+        for mode in [BlendMode::Normal, BlendMode::Multiply] {
+            for n in [0, 1, 2, 7, 64] {
+                let bg = random_pixels(&mut rng, n);
+                for fg in random_pixels(&mut rng, 5) {
+                    let mut span: Vec<Pixel> = bg.iter().map(|&c| c.into()).collect();
+                    blend_span(mode, &mut span, fg);
+                    for (actual, bg) in span.iter().zip(&bg) {
+                        let expected = Pixel::from(mode.blend(*bg, fg));
+                        assert_relative_eq!(*actual, expected, epsilon = 1e-5);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_blend_span_matches_blend() {
+        check_matches_blend(5, |mode, span, fg| mode.blend_span(span, fg));
+    }
+
+    // `blend_span` only reaches the scalar loop on CPUs without AVX, so it is called
+    // directly here.
+    #[test]
+    fn test_scalar_fallback_matches_blend() {
+        check_matches_blend(7, blend_span_scalar);
+    }
+
+    #[test]
+    fn test_blend_span_matches_scalar_path() {
+        let mut rng = crate::utils::Rng::new(6);
+        for mode in [BlendMode::Normal, BlendMode::Multiply] {
+            let bg: Vec<Pixel> = random_pixels(&mut rng, 33)
+                .into_iter()
+                .map(Pixel::from)
+                .collect();
+            for fg in random_pixels(&mut rng, 5) {
+                let mut span = bg.clone();
+                mode.blend_span(&mut span, fg);
+                let mut scalar = bg.clone();
+                blend_span_scalar(mode, &mut scalar, fg);
+                assert_eq!(span, scalar);
+            }
+        }
+    }
+
+    #[test]
+    fn test_blend_each() {
+        let bg = LinSrgba::new(0.2, 0.4, 0.6, 0.5);
+        let colors = [
+            LinSrgba::new(1.0, 0.0, 0.5, 0.25),
+            LinSrgba::new(0.0, 1.0, 0.0, 1.0),
+        ];
+        for mode in [BlendMode::Normal, BlendMode::Multiply] {
+            let mut span = vec![Pixel::from(bg); 2];
+            mode.blend_each(&mut span, colors);
+            for (actual, fg) in span.iter().zip(colors) {
+                assert_relative_eq!(LinSrgba::from(*actual), mode.blend(bg, fg), epsilon = 1e-6);
+            }
+        }
+    }
+}
@@ -0,0 +1,543 @@
+pub mod background;
+pub mod blend;
+pub mod paint;
+pub mod pyramid;
+pub mod resize;
+pub mod view;
+
+use image::Rgba32FImage;
+use ndarray::Array2;
+use palette::{
+    blend::PreAlpha,
+    cast::{self, ComponentsInto, IntoComponents},
+    rgb::{LinSrgb, LinSrgba, Srgba},
+};
+use rayon::iter::Either;
+use rayon::prelude::*;
+
+use crate::error::{Error, Result};
+
+pub use blend::BlendMode;
+pub use paint::{Gradient, GradientShape, GradientStop, Interpolation, Paint};
+pub use pyramid::Pyramid;
+pub use resize::ResizeFilter;
+pub use view::{CanvasView, CanvasViewMut};
+
+/// A canvas pixel: linear sRGB with the color premultiplied by alpha. The slice
+/// accessors (`pixels`, `row`, `rows`, `par_rows` and their views) hand out `Pixel`s as
+/// stored, while `get_pixel` and `put_pixel` take straight colors. Convert between the
+/// two with `LinSrgba::from` and `Pixel::from`.
+pub type Pixel = PreAlpha<LinSrgb<f32>>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CanvasSpec {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl CanvasSpec {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    // This is the underlying vector holding the canvas content.
+    // It should have the row major, or "c" order, memory layout.
+    // Dimention order is [H, W, C], so pixel (x, y) starts at (y * width + x) * 4.
+    // Note that C = 4 here for pre-alpha color to speed up blending: compositing a
+    // premultiplied pixel is a multiply-add per channel, with no division.
+    buff: Vec<f32>,
+    spec: CanvasSpec,
+}
+
+impl Canvas {
+    /// A fully transparent black canvas.
+    pub fn from_spec(spec: CanvasSpec) -> Self {
+        Canvas {
+            buff: vec![0f32; spec.width * spec.height * 4], // RGBA
+            spec,
+        }
+    }
+
+    pub fn from_wh(width: usize, height: usize) -> Self {
+        Self::from_spec(CanvasSpec::new(width, height))
+    }
+
+    pub fn get_spec(&self) -> CanvasSpec {
+        self.spec
+    }
+
+    /// Takes straight-alpha linear colors.
+    pub fn from_image(image: Rgba32FImage) -> Self {
+        let width = image.width() as usize;
+        let height = image.height() as usize;
+        Self::from_straight(
+            CanvasSpec::new(width, height),
+            image.into_raw().components_into(),
+        )
+    }
+
+    /// Takes an image with sRGB-encoded colors, as decoded from a file, and converts
+    /// it to linear light.
+    pub fn from_srgb_image(image: Rgba32FImage) -> Self {
+        let width = image.width() as usize;
+        let height = image.height() as usize;
+        let colors: Vec<Srgba<f32>> = image.into_raw().components_into();
+        let colors = colors.into_iter().map(|c| c.into_linear()).collect();
+        Self::from_straight(CanvasSpec::new(width, height), colors)
+    }
+
+    pub fn from_array2(array: Array2<LinSrgba<f32>>) -> Self {
+        let (height, width) = array.dim();
+        let array = if !array.is_standard_layout() {
+            // This will copy array using the standard layout
+            array.as_standard_layout().into_owned()
+        } else {
+            array
+        };
+        Self::from_straight(CanvasSpec { width, height }, array.into_raw_vec())
+    }
+
+    fn from_straight(spec: CanvasSpec, colors: Vec<LinSrgba<f32>>) -> Self {
+        let pixels: Vec<Pixel> = colors.into_iter().map(Pixel::from).collect();
+        Canvas {
+            buff: pixels.into_components(),
+            spec,
+        }
+    }
+
+    fn into_straight(self) -> Vec<LinSrgba<f32>> {
+        self.pixels().iter().map(|&p| LinSrgba::from(p)).collect()
+    }
+
+    pub fn width(&self) -> usize {
+        self.spec.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.spec.height
+    }
+
+    /// All pixels in row-major order, premultiplied.
+    pub fn pixels(&self) -> &[Pixel] {
+        cast::from_component_slice(&self.buff)
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [Pixel] {
+        cast::from_component_slice_mut(&mut self.buff)
+    }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<LinSrgba<f32>> {
+        if x < self.spec.width && y < self.spec.height {
+            Some(self.pixels()[y * self.spec.width + x].into())
+        } else {
+            None
+        }
+    }
+
+    pub fn put_pixel(&mut self, x: usize, y: usize, color: LinSrgba<f32>) -> Result<()> {
+        if x < self.spec.width && y < self.spec.height {
+            let width = self.spec.width;
+            self.pixels_mut()[y * width + x] = color.into();
+            Ok(())
+        } else {
+            Err(Error::OutOfBounds {
+                x,
+                y,
+                width: self.spec.width,
+                height: self.spec.height,
+            })
+        }
+    }
+
+    /// Row `y`, premultiplied. See `Pixel`.
+    pub fn row(&self, y: usize) -> Option<&[Pixel]> {
+        let width = self.spec.width;
+        if y < self.spec.height {
+            Some(&self.pixels()[y * width..(y + 1) * width])
+        } else {
+            None
+        }
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> Option<&mut [Pixel]> {
+        let width = self.spec.width;
+        if y < self.spec.height {
+            Some(&mut self.pixels_mut()[y * width..(y + 1) * width])
+        } else {
+            None
+        }
+    }
+
+    /// `height` rows of `width` premultiplied pixels, empty ones on a zero-width canvas.
+    pub fn rows(&self) -> impl Iterator<Item = &[Pixel]> + '_ {
+        let width = self.spec.width;
+        (0..self.spec.height).map(move |y| &self.pixels()[y * width..(y + 1) * width])
+    }
+
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [Pixel]> + '_ {
+        let (width, height) = (self.spec.width, self.spec.height);
+        let mut rest = self.pixels_mut();
+        (0..height).map(move |_| {
+            let (row, tail) = std::mem::take(&mut rest).split_at_mut(width);
+            rest = tail;
+            row
+        })
+    }
+
+    /// A bounds-checked rectangular region with its top-left pixel at `(x, y)`.
+    pub fn view(&self, x: usize, y: usize, width: usize, height: usize) -> Result<CanvasView<'_>> {
+        view::check_region(self.spec.width, self.spec.height, x, y, width, height)?;
+        Ok(CanvasView::new(
+            self.pixels(),
+            self.spec.width,
+            x,
+            y,
+            width,
+            height,
+        ))
+    }
+
+    pub fn view_mut(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Result<CanvasViewMut<'_>> {
+        view::check_region(self.spec.width, self.spec.height, x, y, width, height)?;
+        let stride = self.spec.width;
+        Ok(CanvasViewMut::new(
+            self.pixels_mut(),
+            stride,
+            x,
+            y,
+            width,
+            height,
+        ))
+    }
+
+    /// Rows paired with their index, processed in parallel. Like `rows`, a zero-width
+    /// canvas still has `height` of them.
+    pub fn par_rows(&self) -> impl IndexedParallelIterator<Item = (usize, &[Pixel])> {
+        let width = self.spec.width;
+        (0..self.spec.height)
+            .into_par_iter()
+            .map(move |y| (y, &self.pixels()[y * width..(y + 1) * width]))
+    }
+
+    pub fn par_rows_mut(&mut self) -> impl IndexedParallelIterator<Item = (usize, &mut [Pixel])> {
+        let (width, height) = (self.spec.width, self.spec.height);
+        // Empty rows cannot be chunked out of the buffer
+        if width == 0 {
+            Either::Left(
+                (0..height)
+                    .into_par_iter()
+                    .map(|y| (y, <&mut [Pixel]>::default())),
+            )
+        } else {
+            Either::Right(self.pixels_mut().par_chunks_exact_mut(width).enumerate())
+        }
+    }
+
+    /// Splits the canvas into `tile_width` x `tile_height` tiles, processed in parallel.
+    /// Tiles on the right and bottom edges may be smaller.
+    pub fn par_tiles(
+        &self,
+        tile_width: usize,
+        tile_height: usize,
+    ) -> impl IndexedParallelIterator<Item = CanvasView<'_>> {
+        let tile_width = tile_width.max(1);
+        let tile_height = tile_height.max(1);
+        let CanvasSpec { width, height } = self.spec;
+        let cols = width.div_ceil(tile_width);
+        let rows = height.div_ceil(tile_height);
+        (0..cols * rows).into_par_iter().map(move |i| {
+            let x = (i % cols) * tile_width;
+            let y = (i / cols) * tile_height;
+            CanvasView::new(
+                self.pixels(),
+                width,
+                x,
+                y,
+                tile_width.min(width - x),
+                tile_height.min(height - y),
+            )
+        })
+    }
+
+    /// Splits the canvas into full-width horizontal bands of `band_height` rows,
+    /// processed in parallel. The last band may be shorter.
+    pub fn par_bands_mut(
+        &mut self,
+        band_height: usize,
+    ) -> impl IndexedParallelIterator<Item = CanvasViewMut<'_>> {
+        let band_height = band_height.max(1);
+        let width = self.spec.width;
+        self.pixels_mut()
+            .par_chunks_mut((width * band_height).max(1))
+            .enumerate()
+            .map(move |(i, band)| {
+                let height = band.len() / width.max(1);
+                CanvasViewMut::from_band(band, width, (0, i * band_height), width, height)
+            })
+    }
+
+    fn shape_mismatch(&self) -> Error {
+        Error::ShapeMismatch {
+            expected: vec![self.spec.height, self.spec.width, 4],
+            actual: vec![self.buff.len()],
+        }
+    }
+
+    /// Gives straight-alpha linear colors, the inverse of `from_image`.
+    pub fn into_image(self) -> Result<Rgba32FImage> {
+        let err = self.shape_mismatch();
+        match (
+            u32::try_from(self.spec.width),
+            u32::try_from(self.spec.height),
+        ) {
+            (Ok(width), Ok(height)) => {
+                Rgba32FImage::from_raw(width, height, self.into_straight().into_components())
+                    .ok_or(err)
+            }
+            _ => Err(err),
+        }
+    }
+
+    pub fn into_array2(self) -> Result<Array2<LinSrgba>> {
+        let err = self.shape_mismatch();
+        let spec = self.spec;
+        Array2::from_shape_vec((spec.height, spec.width), self.into_straight()).map_err(|_| err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use image::Rgba32FImage;
+    use ndarray::Array2;
+    use palette::rgb::LinSrgba;
+
+    #[test]
+    fn test_canvas_creation() {
+        let spec = CanvasSpec::new(10, 20);
+        let canvas = Canvas::from_spec(spec);
+        assert_eq!(canvas.get_spec().width, 10);
+        assert_eq!(canvas.get_spec().height, 20);
+        assert_eq!(canvas.buff.len(), 10 * 20 * 4);
+    }
+
+    #[test]
+    fn test_into_image() {
+        let spec = CanvasSpec::new(10, 20);
+        let canvas = Canvas::from_spec(spec);
+        let image = canvas.into_image().unwrap();
+        assert_eq!(image.width(), 10);
+        assert_eq!(image.height(), 20);
+        assert_eq!(image.as_raw().len(), 10 * 20 * 4);
+    }
+
+    #[test]
+    fn test_into_array2() {
+        let spec = CanvasSpec::new(10, 20);
+        let canvas = Canvas::from_spec(spec);
+        let array = canvas.into_array2().unwrap();
+        assert_eq!(array.shape(), &[20, 10]);
+    }
+
+    #[test]
+    fn test_from_image() {
+        let width = 10u32;
+        let height = 20u32;
+        let image = Rgba32FImage::from_raw(width, height, vec![0.0; (width * height * 4) as usize])
+            .unwrap();
+        let canvas = Canvas::from_image(image);
+        assert_eq!(canvas.get_spec().width, width as usize);
+        assert_eq!(canvas.get_spec().height, height as usize);
+        assert_eq!(canvas.buff.len(), (width * height * 4) as usize);
+    }
+
+    #[test]
+    fn test_from_srgb_image() {
+        let image = Rgba32FImage::from_raw(1, 1, vec![0.5, 1.0, 0.0, 0.5]).unwrap();
+        let pixel = Canvas::from_srgb_image(image).get_pixel(0, 0).unwrap();
+        // sRGB mid gray is about a fifth of the light of white
+        assert_relative_eq!(pixel.red, 0.21404, epsilon = 1e-5);
+        assert_relative_eq!(pixel.green, 1.0);
+        assert_eq!(pixel.blue, 0.0);
+        assert_eq!(pixel.alpha, 0.5);
+    }
+
+    #[test]
+    fn test_from_array2() {
+        let width = 10;
+        let height = 20;
+        let array = Array2::<LinSrgba<f32>>::from_elem(
+            (height, width),
+            LinSrgba::new(0f32, 0f32, 0f32, 0f32),
+        );
+        let canvas = Canvas::from_array2(array);
+        assert_eq!(canvas.get_spec().width, width);
+        assert_eq!(canvas.get_spec().height, height);
+        assert_eq!(canvas.buff.len(), width * height * 4);
+    }
+
+    #[test]
+    fn test_conversion_round_trip() {
+        let spec = CanvasSpec::new(10, 20);
+        let canvas = Canvas::from_spec(spec);
+
+        // Convert to image and back
+        let image = canvas.clone().into_image().unwrap();
+        let canvas_from_image = Canvas::from_image(image);
+        assert_eq!(canvas.buff, canvas_from_image.buff);
+        assert_eq!(canvas.get_spec(), canvas_from_image.get_spec());
+
+        // Convert to array and back
+        let array = canvas.clone().into_array2().unwrap();
+        let canvas_from_array = Canvas::from_array2(array);
+        assert_eq!(canvas.buff, canvas_from_array.buff);
+        assert_eq!(canvas.get_spec(), canvas_from_array.get_spec());
+    }
+
+    #[test]
+    fn test_image_modification() {
+        let spec = CanvasSpec::new(10, 20);
+        let mut canvas = Canvas::from_spec(spec);
+
+        // Modify a specific pixel
+        let color = LinSrgba::new(1.0, 0.5, 0.25, 0.75);
+        canvas.put_pixel(3, 5, color).unwrap();
+
+        // Convert to image and check the pixel
+        let image = canvas.into_image().unwrap();
+        assert_eq!(image.get_pixel(3, 5).0, [1.0, 0.5, 0.25, 0.75]);
+    }
+
+    #[test]
+    fn test_array_modification() {
+        let spec = CanvasSpec::new(30, 20);
+        let mut canvas = Canvas::from_spec(spec);
+
+        // Modify a specific pixel
+        canvas
+            .put_pixel(2, 11, LinSrgba::new(0.8, 0.6, 0.3, 0.4))
+            .unwrap();
+
+        // Convert to array and check the pixel
+        let array = canvas.into_array2().unwrap();
+        let pixel = array[(11, 2)];
+        // Stored premultiplied, so the color may round on the way back
+        assert_relative_eq!(pixel.color.red, 0.8);
+        assert_relative_eq!(pixel.color.green, 0.6);
+        assert_relative_eq!(pixel.color.blue, 0.3);
+        assert_eq!(pixel.alpha, 0.4);
+    }
+
+    #[test]
+    fn test_pixels_are_premultiplied() {
+        let mut canvas = Canvas::from_wh(2, 1);
+        canvas
+            .put_pixel(0, 0, LinSrgba::new(1.0, 0.5, 0.25, 0.5))
+            .unwrap();
+        canvas
+            .put_pixel(1, 0, LinSrgba::new(1.0, 1.0, 1.0, 0.0))
+            .unwrap();
+        let stored: [f32; 4] = cast::into_array(canvas.pixels()[0]);
+        assert_eq!(stored, [0.5, 0.25, 0.125, 0.5]);
+        assert_eq!(
+            canvas.get_pixel(0, 0),
+            Some(LinSrgba::new(1.0, 0.5, 0.25, 0.5))
+        );
+        // A fully transparent pixel keeps no color
+        assert_eq!(
+            canvas.get_pixel(1, 0),
+            Some(LinSrgba::new(0.0, 0.0, 0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn test_rows_are_premultiplied() {
+        let mut canvas = Canvas::from_wh(3, 2);
+        let color = LinSrgba::new(0.8, 0.4, 0.2, 0.5);
+        canvas.put_pixel(1, 1, color).unwrap();
+        let pixel = canvas.row(1).unwrap()[1];
+        assert_eq!((pixel.red, pixel.green, pixel.blue), (0.4, 0.2, 0.1));
+        assert_eq!(pixel.alpha, 0.5);
+        assert_eq!(LinSrgba::from(pixel), color);
+        assert_eq!(canvas.rows().nth(1).unwrap()[1], Pixel::from(color));
+        let (_, row) = canvas.par_rows().find_first(|&(y, _)| y == 1).unwrap();
+        assert_eq!(row[1], pixel);
+    }
+
+    #[test]
+    fn test_pixel_bounds() {
+        let mut canvas = Canvas::from_wh(4, 3);
+        let color = LinSrgba::new(0.1, 0.2, 0.3, 1.0);
+        assert!(canvas.get_pixel(4, 0).is_none());
+        assert!(canvas.get_pixel(0, 3).is_none());
+        assert!(matches!(
+            canvas.put_pixel(4, 0, color),
+            Err(Error::OutOfBounds { x: 4, y: 0, .. })
+        ));
+        canvas.put_pixel(3, 2, color).unwrap();
+        assert_eq!(canvas.get_pixel(3, 2), Some(color));
+        assert_eq!(LinSrgba::from(canvas.row(2).unwrap()[3]), color);
+        assert!(canvas.row(3).is_none());
+    }
+
+    #[test]
+    fn test_par_rows_mut() {
+        let mut canvas = Canvas::from_wh(7, 5);
+        canvas.par_rows_mut().for_each(|(y, row)| {
+            row.fill(LinSrgba::new(y as f32, 0.0, 0.0, 1.0).into());
+        });
+        assert_eq!(canvas.rows().count(), 5);
+        for (y, row) in canvas.rows().enumerate() {
+            assert!(row.iter().all(|p| p.red == y as f32));
+        }
+    }
+
+    #[test]
+    fn test_zero_width_rows() {
+        let mut canvas = Canvas::from_wh(0, 3);
+        assert_eq!(canvas.rows().count(), canvas.height());
+        assert_eq!(canvas.rows_mut().count(), 3);
+        assert_eq!(canvas.par_rows().count(), 3);
+        let rows: Vec<_> = canvas
+            .par_rows_mut()
+            .map(|(y, row)| (y, row.len()))
+            .collect();
+        assert_eq!(rows, vec![(0, 0), (1, 0), (2, 0)]);
+        assert!(canvas.rows().all(|row| row.is_empty()));
+    }
+
+    #[test]
+    fn test_par_tiles_cover_canvas() {
+        let canvas = Canvas::from_wh(10, 7);
+        let tiles: Vec<_> = canvas.par_tiles(4, 3).collect();
+        assert_eq!(tiles.len(), 3 * 3);
+        let area: usize = tiles.iter().map(|t| t.width() * t.height()).sum();
+        assert_eq!(area, 10 * 7);
+        assert_eq!(tiles[8].origin(), (8, 6));
+        assert_eq!((tiles[8].width(), tiles[8].height()), (2, 1));
+    }
+
+    #[test]
+    fn test_par_bands_mut() {
+        let mut canvas = Canvas::from_wh(6, 7);
+        canvas.par_bands_mut(3).for_each(|mut band| {
+            let y0 = band.origin().1;
+            for (dy, row) in band.rows_mut().enumerate() {
+                row.fill(LinSrgba::new((y0 + dy) as f32, 0.0, 0.0, 1.0).into());
+            }
+        });
+        for y in 0..7 {
+            assert_eq!(canvas.get_pixel(5, y).unwrap().red, y as f32);
+        }
+    }
+}
@@ -0,0 +1,416 @@
+use palette::{rgb::LinSrgba, FromColor, Mix, Oklaba};
+
+use crate::error::Result;
+use crate::geometry::primitive::check_params;
+use crate::geometry::{Affine2, Point, Transform};
+
+/// The color space gradients blend in between stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    #[default]
+    LinearLight,
+    // Perceptually even steps, avoiding the dull middle of linear blends
+    Oklab,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradientStop {
+    /// Position along the gradient, from 0 to 1.
+    pub offset: f64,
+    pub color: LinSrgba<f64>,
+}
+
+impl GradientStop {
+    pub fn new(offset: f64, color: LinSrgba<f64>) -> Self {
+        GradientStop { offset, color }
+    }
+}
+
+/// Where a gradient's offsets lie, with the same conventions as the canvas gradients.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradientShape {
+    /// Offset 0 at `start` and 1 at `end`, constant along lines perpendicular to
+    /// `start -> end`.
+    Linear { start: Point<f64>, end: Point<f64> },
+    /// Offset 0 at `center` and 1 at `radius`.
+    Radial { center: Point<f64>, radius: f64 },
+    /// A radial gradient under a non-uniform transform: offset 0 at `center` and 1 on
+    /// the ellipse through `center + u` and `center + v`, where `u` and `v` are the
+    /// images of two perpendicular radii.
+    Elliptical {
+        center: Point<f64>,
+        u: Point<f64>,
+        v: Point<f64>,
+    },
+}
+
+/// Colors interpolated between stops sorted by offset. Offsets past the first or last
+/// stop take its color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub shape: GradientShape,
+    pub stops: Vec<GradientStop>,
+    pub interpolation: Interpolation,
+}
+
+impl Gradient {
+    pub fn linear(start: Point<f64>, end: Point<f64>, stops: Vec<GradientStop>) -> Self {
+        Gradient {
+            shape: GradientShape::Linear { start, end },
+            stops,
+            interpolation: Interpolation::default(),
+        }
+    }
+
+    pub fn radial(center: Point<f64>, radius: f64, stops: Vec<GradientStop>) -> Self {
+        Gradient {
+            shape: GradientShape::Radial { center, radius },
+            stops,
+            interpolation: Interpolation::default(),
+        }
+    }
+
+    pub fn with_interpolation(self, interpolation: Interpolation) -> Self {
+        Gradient {
+            interpolation,
+            ..self
+        }
+    }
+
+    /// The offset at `p`, clamped to [0, 1].
+    pub fn offset_at(&self, p: Point<f64>) -> f64 {
+        let t = match self.shape {
+            GradientShape::Linear { start, end } => {
+                let (d, v) = (end - start, p - start);
+                let len_sq = d.x * d.x + d.y * d.y;
+                if len_sq > 0.0 {
+                    (v.x * d.x + v.y * d.y) / len_sq
+                } else {
+                    0.0
+                }
+            }
+            GradientShape::Radial { center, radius } => {
+                let d = p - center;
+                if radius > 0.0 {
+                    d.x.hypot(d.y) / radius
+                } else {
+                    1.0
+                }
+            }
+            GradientShape::Elliptical { center, u, v } => {
+                // Solve d = a * u + b * v, the offset being the length of (a, b)
+                let (d, det) = (p - center, u.x * v.y - u.y * v.x);
+                if det != 0.0 {
+                    let a = (d.x * v.y - d.y * v.x) / det;
+                    let b = (u.x * d.y - u.y * d.x) / det;
+                    a.hypot(b)
+                } else {
+                    1.0
+                }
+            }
+        };
+        t.clamp(0.0, 1.0)
+    }
+
+    /// The color at offset `t`. Without stops, everything is transparent.
+    pub fn color_at_offset(&self, t: f64) -> LinSrgba<f64> {
+        let next = self.stops.partition_point(|stop| stop.offset <= t);
+        let (a, b) = match (self.stops.get(next.wrapping_sub(1)), self.stops.get(next)) {
+            (Some(a), Some(b)) => (a, b),
+            (Some(stop), None) | (None, Some(stop)) => return stop.color,
+            (None, None) => return LinSrgba::new(0.0, 0.0, 0.0, 0.0),
+        };
+        // `a.offset <= t < b.offset`, so the division is safe
+        let u = (t - a.offset) / (b.offset - a.offset);
+        match self.interpolation {
+            Interpolation::LinearLight => a.color.mix(b.color, u),
+            Interpolation::Oklab => {
+                let (a, b) = (Oklaba::from_color(a.color), Oklaba::from_color(b.color));
+                LinSrgba::from_color(a.mix(b, u))
+            }
+        }
+    }
+
+    pub fn color_at(&self, p: Point<f64>) -> LinSrgba<f64> {
+        self.color_at_offset(self.offset_at(p))
+    }
+}
+
+// Every gradient is mapped exactly. The offset of a linear gradient is an affine
+// function of the point, which stays affine after the transform. Radial gradients stay
+// radial under similarities and become elliptical otherwise.
+impl Transform for Gradient {
+    fn transform(&self, t: &Affine2) -> Self {
+        let shape = match self.shape {
+            GradientShape::Linear { start, end } => {
+                // The offset's gradient g = d / |d|^2 maps to the inverse transpose of `t`
+                let d = end - start;
+                let len_sq = d.x * d.x + d.y * d.y;
+                let mapped = t.apply(start);
+                let g = match t.invert() {
+                    Some(inv) if len_sq > 0.0 => Point::new(
+                        (inv.a * d.x + inv.d * d.y) / len_sq,
+                        (inv.b * d.x + inv.e * d.y) / len_sq,
+                    ),
+                    _ => Point::new(0.0, 0.0),
+                };
+                let g_sq = g.x * g.x + g.y * g.y;
+                GradientShape::Linear {
+                    start: mapped,
+                    end: if g_sq > 0.0 {
+                        mapped + g * (1.0 / g_sq)
+                    } else {
+                        mapped
+                    },
+                }
+            }
+            GradientShape::Radial { center, radius } => {
+                let mapped = t.apply(center);
+                let u = t.apply(center + Point::new(radius, 0.0)) - mapped;
+                let v = t.apply(center + Point::new(0.0, radius)) - mapped;
+                let (su, sv) = (u.x.hypot(u.y), v.x.hypot(v.y));
+                let similar = (su - sv).abs() <= 1e-12 * su.max(sv)
+                    && (u.x * v.x + u.y * v.y).abs() <= 1e-12 * su * sv;
+                if similar {
+                    GradientShape::Radial {
+                        center: mapped,
+                        radius: su,
+                    }
+                } else {
+                    GradientShape::Elliptical {
+                        center: mapped,
+                        u,
+                        v,
+                    }
+                }
+            }
+            GradientShape::Elliptical { center, u, v } => {
+                let mapped = t.apply(center);
+                GradientShape::Elliptical {
+                    center: mapped,
+                    u: t.apply(center + u) - mapped,
+                    v: t.apply(center + v) - mapped,
+                }
+            }
+        };
+        Gradient {
+            shape,
+            ..self.clone()
+        }
+    }
+}
+
+/// How a shape is filled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Paint {
+    Solid(LinSrgba<f64>),
+    Gradient(Gradient),
+}
+
+impl From<LinSrgba<f64>> for Paint {
+    fn from(color: LinSrgba<f64>) -> Self {
+        Paint::Solid(color)
+    }
+}
+
+impl From<Gradient> for Paint {
+    fn from(gradient: Gradient) -> Self {
+        Paint::Gradient(gradient)
+    }
+}
+
+impl Paint {
+    pub fn color_at(&self, p: Point<f64>) -> LinSrgba<f64> {
+        match self {
+            Paint::Solid(color) => *color,
+            Paint::Gradient(gradient) => gradient.color_at(p),
+        }
+    }
+
+    /// Whether every point is painted fully opaque.
+    pub fn is_opaque(&self) -> bool {
+        match self {
+            Paint::Solid(color) => color.alpha >= 1.0,
+            Paint::Gradient(gradient) => {
+                !gradient.stops.is_empty()
+                    && gradient.stops.iter().all(|stop| stop.color.alpha >= 1.0)
+            }
+        }
+    }
+
+    /// Fails on the first gradient parameter that is NaN or infinite.
+    pub fn check_finite(&self) -> Result<()> {
+        let Paint::Gradient(gradient) = self else {
+            return Ok(());
+        };
+        match gradient.shape {
+            GradientShape::Linear { start, end } => check_params(&[
+                ("start.x", start.x),
+                ("start.y", start.y),
+                ("end.x", end.x),
+                ("end.y", end.y),
+            ])?,
+            GradientShape::Radial { center, radius } => check_params(&[
+                ("center.x", center.x),
+                ("center.y", center.y),
+                ("radius", radius),
+            ])?,
+            GradientShape::Elliptical { center, u, v } => check_params(&[
+                ("center.x", center.x),
+                ("center.y", center.y),
+                ("u.x", u.x),
+                ("u.y", u.y),
+                ("v.x", v.x),
+                ("v.y", v.y),
+            ])?,
+        }
+        let offsets: Vec<_> = gradient
+            .stops
+            .iter()
+            .map(|s| ("offset", s.offset))
+            .collect();
+        check_params(&offsets)
+    }
+}
+
+impl Transform for Paint {
+    fn transform(&self, t: &Affine2) -> Self {
+        match self {
+            Paint::Solid(color) => Paint::Solid(*color),
+            Paint::Gradient(gradient) => Paint::Gradient(gradient.transform(t)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod paint_tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn black_to_white() -> Vec<GradientStop> {
+        vec![
+            GradientStop::new(0.0, LinSrgba::new(0.0, 0.0, 0.0, 1.0)),
+            GradientStop::new(1.0, LinSrgba::new(1.0, 1.0, 1.0, 1.0)),
+        ]
+    }
+
+    #[test]
+    fn test_linear_gradient() {
+        let gradient = Gradient::linear(
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 0.0),
+            black_to_white(),
+        );
+        assert_relative_eq!(gradient.offset_at(Point::new(2.5, 7.0)), 0.25);
+        assert_relative_eq!(gradient.color_at(Point::new(2.5, 7.0)).red, 0.25);
+        assert_eq!(gradient.color_at(Point::new(-3.0, 0.0)).red, 0.0);
+        assert_eq!(gradient.color_at(Point::new(30.0, 0.0)).red, 1.0);
+        assert!(Paint::from(gradient).is_opaque());
+    }
+
+    #[test]
+    fn test_stops() {
+        let red = LinSrgba::new(1.0, 0.0, 0.0, 1.0);
+        let blue = LinSrgba::new(0.0, 0.0, 1.0, 0.5);
+        let mut stops = black_to_white();
+        stops.insert(1, GradientStop::new(0.5, red));
+        stops.insert(2, GradientStop::new(0.5, blue));
+        let gradient = Gradient::radial(Point::new(0.0, 0.0), 4.0, stops);
+        // A repeated offset makes a hard edge
+        assert_relative_eq!(gradient.color_at_offset(0.49999).red, 0.99998);
+        assert_eq!(gradient.color_at(Point::new(0.0, 2.0)), blue);
+        assert_relative_eq!(gradient.color_at(Point::new(3.0, 0.0)).alpha, 0.75);
+        assert!(!Paint::from(gradient).is_opaque());
+
+        let empty = Gradient::radial(Point::new(0.0, 0.0), 4.0, Vec::new());
+        assert_eq!(empty.color_at_offset(0.5).alpha, 0.0);
+    }
+
+    #[test]
+    fn test_oklab_interpolation() {
+        let gradient =
+            Gradient::linear(Point::new(0.0, 0.0), Point::new(1.0, 0.0), black_to_white());
+        let oklab = gradient.clone().with_interpolation(Interpolation::Oklab);
+        for t in [0.0, 1.0] {
+            assert_relative_eq!(
+                oklab.color_at_offset(t).red,
+                gradient.color_at_offset(t).red,
+                epsilon = 1e-6
+            );
+        }
+        // Oklab lightness is close to perceived lightness, which is far below half of
+        // the linear light in the middle
+        let mid = oklab.color_at_offset(0.5);
+        assert!(mid.red < 0.25 && mid.red > 0.1, "{:?}", mid);
+        assert_relative_eq!(mid.red, mid.blue, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_transform() {
+        let gradient =
+            Gradient::linear(Point::new(1.0, 2.0), Point::new(4.0, 6.0), black_to_white());
+        let t = Affine2::shear(0.5, 0.2)
+            .then(&Affine2::scale(2.0, 3.0))
+            .then(&Affine2::translate(-1.0, 5.0));
+        let mapped = gradient.transform(&t);
+        for p in [
+            Point::new(0.0, 0.0),
+            Point::new(3.0, 3.0),
+            Point::new(2.0, 5.0),
+        ] {
+            assert_relative_eq!(
+                mapped.offset_at(t.apply(p)),
+                gradient.offset_at(p),
+                epsilon = 1e-9
+            );
+        }
+
+        let radial = Paint::from(Gradient::radial(
+            Point::new(1.0, 1.0),
+            2.0,
+            black_to_white(),
+        ));
+        match radial.transform(&Affine2::scale(3.0, 3.0)) {
+            Paint::Gradient(Gradient {
+                shape: GradientShape::Radial { center, radius },
+                ..
+            }) => {
+                assert_eq!(center, Point::new(3.0, 3.0));
+                assert_relative_eq!(radius, 6.0);
+            }
+            other => panic!("{:?}", other),
+        }
+
+        // A stretched radial gradient keeps its offsets at the mapped points
+        let radial = Gradient::radial(Point::new(1.0, 1.0), 2.0, black_to_white());
+        let t = Affine2::rotate(0.4)
+            .then(&Affine2::scale(3.0, 0.5))
+            .then(&Affine2::shear(0.3, 0.0))
+            .then(&Affine2::translate(2.0, -1.0));
+        let mapped = radial.transform(&t);
+        assert!(matches!(mapped.shape, GradientShape::Elliptical { .. }));
+        for p in [
+            Point::new(1.0, 1.0),
+            Point::new(2.0, 1.5),
+            Point::new(-0.5, 1.0),
+            Point::new(4.0, 4.0),
+        ] {
+            assert_relative_eq!(
+                mapped.offset_at(t.apply(p)),
+                radial.offset_at(p),
+                epsilon = 1e-9
+            );
+        }
+        let back = mapped.transform(&t.invert().unwrap());
+        assert_relative_eq!(back.offset_at(Point::new(2.0, 1.5)), 0.5590169943749475);
+
+        let mut bad = Gradient::radial(Point::new(1.0, 1.0), f64::NAN, black_to_white());
+        assert!(Paint::from(bad.clone()).check_finite().is_err());
+        bad.shape = GradientShape::Radial {
+            center: Point::new(1.0, 1.0),
+            radius: 1.0,
+        };
+        bad.stops[0].offset = f64::INFINITY;
+        assert!(Paint::from(bad).check_finite().is_err());
+    }
+}
@@ -0,0 +1,86 @@
+use super::resize::ResizeFilter;
+use super::{Canvas, CanvasSpec};
+
+/// Successive half-resolution copies of a canvas, from the full-size input at
+/// level 0 down to the coarsest level.
+#[derive(Debug, Clone)]
+pub struct Pyramid {
+    levels: Vec<Canvas>,
+}
+
+impl Pyramid {
+    /// Halves the canvas (rounding up) until the next level would be narrower or
+    /// shorter than `min_size` pixels.
+    pub fn new(base: Canvas, min_size: usize, filter: ResizeFilter) -> Self {
+        let min_size = min_size.max(1);
+        let mut levels = vec![base];
+        loop {
+            let spec = levels[levels.len() - 1].get_spec();
+            if spec.width <= 1 && spec.height <= 1 {
+                break;
+            }
+            let next = CanvasSpec::new(spec.width.div_ceil(2), spec.height.div_ceil(2));
+            if next.width < min_size || next.height < min_size {
+                break;
+            }
+            let level = levels[levels.len() - 1].resize(next, filter);
+            levels.push(level);
+        }
+        Pyramid { levels }
+    }
+
+    pub fn levels(&self) -> &[Canvas] {
+        &self.levels
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    pub fn level(&self, index: usize) -> Option<&Canvas> {
+        self.levels.get(index)
+    }
+
+    pub fn base(&self) -> &Canvas {
+        &self.levels[0]
+    }
+
+    pub fn coarsest(&self) -> &Canvas {
+        &self.levels[self.levels.len() - 1]
+    }
+
+    pub fn into_levels(self) -> Vec<Canvas> {
+        self.levels
+    }
+}
+
+#[cfg(test)]
+mod pyramid_tests {
+    use super::*;
+
+    #[test]
+    fn test_pyramid_level_sizes() {
+        let pyramid = Pyramid::new(Canvas::from_wh(20, 11), 3, ResizeFilter::Box);
+        let specs: Vec<_> = pyramid.levels().iter().map(|c| c.get_spec()).collect();
+        assert_eq!(
+            specs,
+            vec![
+                CanvasSpec::new(20, 11),
+                CanvasSpec::new(10, 6),
+                CanvasSpec::new(5, 3),
+            ]
+        );
+        assert_eq!(pyramid.coarsest().get_spec(), CanvasSpec::new(5, 3));
+    }
+
+    #[test]
+    fn test_pyramid_stops_at_single_pixel() {
+        let pyramid = Pyramid::new(Canvas::from_wh(4, 4), 0, ResizeFilter::Bilinear);
+        assert_eq!(pyramid.len(), 3);
+        assert_eq!(pyramid.coarsest().get_spec(), CanvasSpec::new(1, 1));
+    }
+}
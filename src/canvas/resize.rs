@@ -0,0 +1,240 @@
+use std::f64::consts::PI;
+
+use palette::rgb::LinSrgb;
+use rayon::prelude::*;
+
+use super::{Canvas, CanvasSpec, Pixel};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeFilter {
+    Box,
+    Bilinear,
+    // Catmull-Rom
+    Bicubic,
+    Lanczos3,
+}
+
+impl ResizeFilter {
+    // Radius of the kernel in source pixels, before widening for downsampling.
+    fn support(&self) -> f64 {
+        match self {
+            Self::Box => 0.5,
+            Self::Bilinear => 1.0,
+            Self::Bicubic => 2.0,
+            Self::Lanczos3 => 3.0,
+        }
+    }
+
+    fn weight(&self, x: f64) -> f64 {
+        let x = x.abs();
+        match self {
+            Self::Box => {
+                if x <= 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Self::Bilinear => (1.0 - x).max(0.0),
+            Self::Bicubic => {
+                // Keys cubic with a = -0.5
+                if x < 1.0 {
+                    1.5 * x * x * x - 2.5 * x * x + 1.0
+                } else if x < 2.0 {
+                    -0.5 * x * x * x + 2.5 * x * x - 4.0 * x + 2.0
+                } else {
+                    0.0
+                }
+            }
+            Self::Lanczos3 => {
+                if x < 1e-8 {
+                    1.0
+                } else if x < 3.0 {
+                    let px = PI * x;
+                    3.0 * px.sin() * (px / 3.0).sin() / (px * px)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+// Normalized source weights for one output pixel.
+struct Taps {
+    start: usize,
+    weights: Vec<f32>,
+}
+
+// Computes the taps of every output pixel when resampling `src_len` pixels to `dst_len`.
+// Pixel centers are aligned, and the kernel is widened when downsampling so every
+// source pixel contributes.
+fn compute_taps(src_len: usize, dst_len: usize, filter: ResizeFilter) -> Vec<Taps> {
+    let scale = src_len as f64 / dst_len as f64;
+    let filter_scale = scale.max(1.0);
+    let support = filter.support() * filter_scale;
+    (0..dst_len)
+        .map(|i| {
+            let center = (i as f64 + 0.5) * scale - 0.5;
+            let start = ((center - support).ceil().max(0.0)) as usize;
+            let end = ((center + support).floor() as usize).min(src_len - 1);
+            let mut weights: Vec<f64> = (start..=end)
+                .map(|j| filter.weight((j as f64 - center) / filter_scale))
+                .collect();
+            let total: f64 = weights.iter().sum();
+            if total.abs() < 1e-12 {
+                // Nothing under the kernel, fall back to the nearest pixel
+                let nearest = (center.round().max(0.0) as usize).min(src_len - 1);
+                return Taps {
+                    start: nearest,
+                    weights: vec![1.0],
+                };
+            }
+            weights.iter_mut().for_each(|w| *w /= total);
+            Taps {
+                start,
+                weights: weights.into_iter().map(|w| w as f32).collect(),
+            }
+        })
+        .collect()
+}
+
+// Sums premultiplied colors, so transparent pixels add no color to their neighbors.
+fn weighted_sum<'a>(taps: &Taps, pixel: impl Fn(usize) -> &'a Pixel) -> Pixel {
+    let mut acc = [0f32; 4];
+    for (k, &w) in taps.weights.iter().enumerate() {
+        let p = pixel(taps.start + k);
+        acc[0] += p.red * w;
+        acc[1] += p.green * w;
+        acc[2] += p.blue * w;
+        acc[3] += p.alpha * w;
+    }
+    Pixel {
+        color: LinSrgb::new(acc[0], acc[1], acc[2]),
+        alpha: acc[3],
+    }
+}
+
+impl Canvas {
+    /// Resamples the canvas to `spec` in linear light, filtering premultiplied colors.
+    /// Bicubic and Lanczos may ring slightly outside the input range near hard edges.
+    pub fn resize(&self, spec: CanvasSpec, filter: ResizeFilter) -> Canvas {
+        if self.spec == spec {
+            return self.clone();
+        }
+        if self.spec.width == 0 || self.spec.height == 0 || spec.width == 0 || spec.height == 0 {
+            return Canvas::from_spec(spec);
+        }
+
+        // Horizontal pass: src.width x src.height -> spec.width x src.height
+        let x_taps = compute_taps(self.spec.width, spec.width, filter);
+        let mut horizontal = Canvas::from_wh(spec.width, self.spec.height);
+        horizontal.par_rows_mut().for_each(|(y, row)| {
+            let src = &self.pixels()[y * self.spec.width..(y + 1) * self.spec.width];
+            for (pixel, taps) in row.iter_mut().zip(&x_taps) {
+                *pixel = weighted_sum(taps, |j| &src[j]);
+            }
+        });
+
+        // Vertical pass: spec.width x src.height -> spec.width x spec.height
+        let y_taps = compute_taps(self.spec.height, spec.height, filter);
+        let mut resized = Canvas::from_spec(spec);
+        let src = horizontal.pixels();
+        resized.par_rows_mut().for_each(|(y, row)| {
+            let taps = &y_taps[y];
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = weighted_sum(taps, |j| &src[j * spec.width + x]);
+            }
+        });
+        resized
+    }
+}
+
+#[cfg(test)]
+mod resize_tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use palette::rgb::LinSrgba;
+
+    const FILTERS: [ResizeFilter; 4] = [
+        ResizeFilter::Box,
+        ResizeFilter::Bilinear,
+        ResizeFilter::Bicubic,
+        ResizeFilter::Lanczos3,
+    ];
+
+    #[test]
+    fn test_constant_canvas_stays_constant() {
+        let color = LinSrgba::new(0.2, 0.4, 0.6, 1.0);
+        let canvas = Canvas::from_color(CanvasSpec::new(13, 7), color);
+        for filter in FILTERS {
+            for spec in [CanvasSpec::new(5, 3), CanvasSpec::new(40, 21)] {
+                let resized = canvas.resize(spec, filter);
+                assert_eq!(resized.get_spec(), spec);
+                for pixel in resized.pixels() {
+                    assert_relative_eq!(LinSrgba::from(*pixel), color, epsilon = 1e-5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_box_downsample_averages() {
+        let mut canvas =
+            Canvas::from_color(CanvasSpec::new(4, 2), LinSrgba::new(0.0, 0.0, 0.0, 1.0));
+        canvas
+            .put_pixel(0, 0, LinSrgba::new(1.0, 1.0, 1.0, 1.0))
+            .unwrap();
+        let resized = canvas.resize(CanvasSpec::new(2, 1), ResizeFilter::Box);
+        assert_relative_eq!(resized.get_pixel(0, 0).unwrap().red, 0.25);
+        assert_relative_eq!(resized.get_pixel(1, 0).unwrap().red, 0.0);
+    }
+
+    #[test]
+    fn test_transparent_pixels_do_not_bleed() {
+        let mut canvas =
+            Canvas::from_color(CanvasSpec::new(2, 1), LinSrgba::new(1.0, 0.0, 0.0, 0.0));
+        canvas
+            .put_pixel(1, 0, LinSrgba::new(0.0, 0.0, 1.0, 1.0))
+            .unwrap();
+        for filter in FILTERS {
+            let resized = canvas.resize(CanvasSpec::new(1, 1), filter);
+            let pixel = resized.get_pixel(0, 0).unwrap();
+            // Half covered, and all of the color comes from the opaque pixel
+            assert_relative_eq!(pixel.alpha, 0.5, epsilon = 1e-5);
+            assert_relative_eq!(pixel.red, 0.0, epsilon = 1e-5);
+            assert_relative_eq!(pixel.blue, 1.0, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_box_upsample_is_nearest() {
+        let mut canvas =
+            Canvas::from_color(CanvasSpec::new(2, 1), LinSrgba::new(0.0, 0.0, 0.0, 1.0));
+        canvas
+            .put_pixel(1, 0, LinSrgba::new(1.0, 1.0, 1.0, 1.0))
+            .unwrap();
+        let resized = canvas.resize(CanvasSpec::new(4, 1), ResizeFilter::Box);
+        let reds: Vec<f32> = resized.pixels().iter().map(|p| p.red).collect();
+        assert_eq!(reds, vec![0.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_bilinear_upsample_interpolates() {
+        let mut canvas =
+            Canvas::from_color(CanvasSpec::new(2, 1), LinSrgba::new(0.0, 0.0, 0.0, 1.0));
+        canvas
+            .put_pixel(1, 0, LinSrgba::new(1.0, 1.0, 1.0, 1.0))
+            .unwrap();
+        let resized = canvas.resize(CanvasSpec::new(4, 1), ResizeFilter::Bilinear);
+        let reds: Vec<f32> = resized.pixels().iter().map(|p| p.red).collect();
+        assert_relative_eq!(reds.as_slice(), [0.0, 0.25, 0.75, 1.0].as_slice());
+    }
+
+    #[test]
+    fn test_resize_empty() {
+        let canvas = Canvas::from_wh(0, 3);
+        let resized = canvas.resize(CanvasSpec::new(2, 2), ResizeFilter::Bilinear);
+        assert_eq!(resized.get_spec(), CanvasSpec::new(2, 2));
+    }
+}
@@ -0,0 +1,358 @@
+use palette::rgb::LinSrgba;
+
+use super::Pixel;
+
+use crate::error::{Error, Result};
+
+// A rectangular region of a canvas.
+// `pixels` starts at the top-left pixel of the region and consecutive rows are
+// `stride` pixels apart, so only the first `width` pixels of each row belong to the view.
+#[derive(Debug, Clone, Copy)]
+pub struct CanvasView<'a> {
+    pixels: &'a [Pixel],
+    stride: usize,
+    origin: (usize, usize),
+    width: usize,
+    height: usize,
+}
+
+#[derive(Debug)]
+pub struct CanvasViewMut<'a> {
+    pixels: &'a mut [Pixel],
+    stride: usize,
+    origin: (usize, usize),
+    width: usize,
+    height: usize,
+}
+
+// Checks that the region lies inside a canvas of `canvas_width` x `canvas_height`.
+pub(crate) fn check_region(
+    canvas_width: usize,
+    canvas_height: usize,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+) -> Result<()> {
+    match (x.checked_add(width), y.checked_add(height)) {
+        (Some(x_end), Some(y_end)) if x_end <= canvas_width && y_end <= canvas_height => Ok(()),
+        _ => Err(Error::RegionOutOfBounds {
+            x,
+            y,
+            width,
+            height,
+            canvas_width,
+            canvas_height,
+        }),
+    }
+}
+
+// The slice of a row-major buffer holding the region, starting at its top-left pixel.
+// A zero-width region still spans its rows, so that each of them can be sliced, while
+// one without rows holds nothing and may lie past the end of the buffer.
+fn region_range(stride: usize, x: usize, y: usize, width: usize, height: usize) -> (usize, usize) {
+    let start = y * stride + x;
+    if height == 0 {
+        (0, 0)
+    } else {
+        (start, start + (height - 1) * stride + width)
+    }
+}
+
+macro_rules! impl_view_getters {
+    ($view:ident) => {
+        impl<'a> $view<'a> {
+            pub fn width(&self) -> usize {
+                self.width
+            }
+
+            pub fn height(&self) -> usize {
+                self.height
+            }
+
+            /// Position of the top-left pixel of the view in canvas coordinates.
+            pub fn origin(&self) -> (usize, usize) {
+                self.origin
+            }
+
+            /// Reads the pixel at view-local coordinates.
+            pub fn get_pixel(&self, x: usize, y: usize) -> Option<LinSrgba<f32>> {
+                if x < self.width && y < self.height {
+                    Some(self.pixels[y * self.stride + x].into())
+                } else {
+                    None
+                }
+            }
+
+            /// Row `y` of the view, premultiplied like `Canvas::row`.
+            pub fn row(&self, y: usize) -> Option<&[Pixel]> {
+                if y < self.height {
+                    let start = y * self.stride;
+                    Some(&self.pixels[start..start + self.width])
+                } else {
+                    None
+                }
+            }
+
+            pub fn rows(&self) -> impl Iterator<Item = &[Pixel]> + '_ {
+                (0..self.height).map(move |y| {
+                    let start = y * self.stride;
+                    &self.pixels[start..start + self.width]
+                })
+            }
+
+            /// Iterates over `(x, y, color)` in view-local coordinates, row by row.
+            pub fn enumerate_pixels(
+                &self,
+            ) -> impl Iterator<Item = (usize, usize, LinSrgba<f32>)> + '_ {
+                self.rows().enumerate().flat_map(|(y, row)| {
+                    row.iter()
+                        .enumerate()
+                        .map(move |(x, &color)| (x, y, color.into()))
+                })
+            }
+        }
+    };
+}
+
+impl_view_getters!(CanvasView);
+impl_view_getters!(CanvasViewMut);
+
+impl<'a> CanvasView<'a> {
+    pub(crate) fn new(
+        buff: &'a [Pixel],
+        stride: usize,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Self {
+        let (start, end) = region_range(stride, x, y, width, height);
+        CanvasView {
+            pixels: &buff[start..end],
+            stride,
+            origin: (x, y),
+            width,
+            height,
+        }
+    }
+
+    /// A sub-region of this view, in view-local coordinates.
+    pub fn view(&self, x: usize, y: usize, width: usize, height: usize) -> Result<CanvasView<'a>> {
+        check_region(self.width, self.height, x, y, width, height)?;
+        let mut view = CanvasView::new(self.pixels, self.stride, x, y, width, height);
+        view.origin = (self.origin.0 + x, self.origin.1 + y);
+        Ok(view)
+    }
+}
+
+impl<'a> CanvasViewMut<'a> {
+    pub(crate) fn new(
+        buff: &'a mut [Pixel],
+        stride: usize,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Self {
+        let (start, end) = region_range(stride, x, y, width, height);
+        CanvasViewMut {
+            pixels: &mut buff[start..end],
+            stride,
+            origin: (x, y),
+            width,
+            height,
+        }
+    }
+
+    // Wraps a buffer whose first pixel is already the top-left of the view,
+    // e.g. a band split off a canvas.
+    pub(crate) fn from_band(
+        pixels: &'a mut [Pixel],
+        stride: usize,
+        origin: (usize, usize),
+        width: usize,
+        height: usize,
+    ) -> Self {
+        CanvasViewMut {
+            pixels,
+            stride,
+            origin,
+            width,
+            height,
+        }
+    }
+
+    pub fn as_view(&self) -> CanvasView<'_> {
+        CanvasView {
+            pixels: self.pixels,
+            stride: self.stride,
+            origin: self.origin,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// Writes the pixel at view-local coordinates.
+    pub fn put_pixel(&mut self, x: usize, y: usize, color: LinSrgba<f32>) -> Result<()> {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.stride + x] = color.into();
+            Ok(())
+        } else {
+            Err(Error::OutOfBounds {
+                x,
+                y,
+                width: self.width,
+                height: self.height,
+            })
+        }
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> Option<&mut [Pixel]> {
+        if y < self.height {
+            let start = y * self.stride;
+            Some(&mut self.pixels[start..start + self.width])
+        } else {
+            None
+        }
+    }
+
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [Pixel]> + '_ {
+        let (width, stride) = (self.width, self.stride);
+        let mut rest = &mut *self.pixels;
+        (0..self.height).map(move |_| {
+            let (row, tail) = std::mem::take(&mut rest).split_at_mut(width);
+            // The buffer ends with the last row, so there is no gap after it
+            rest = tail.get_mut(stride - width..).unwrap_or_default();
+            row
+        })
+    }
+}
+
+#[cfg(test)]
+mod view_tests {
+    use crate::canvas::Canvas;
+    use crate::error::Error;
+    use palette::rgb::LinSrgba;
+
+    fn numbered_canvas(width: usize, height: usize) -> Canvas {
+        let mut canvas = Canvas::from_wh(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let color = LinSrgba::new(x as f32, y as f32, 0.0, 1.0);
+                canvas.put_pixel(x, y, color).unwrap();
+            }
+        }
+        canvas
+    }
+
+    #[test]
+    fn test_view_reads_region() {
+        let canvas = numbered_canvas(8, 6);
+        let view = canvas.view(2, 3, 4, 2).unwrap();
+        assert_eq!(view.width(), 4);
+        assert_eq!(view.height(), 2);
+        assert_eq!(view.origin(), (2, 3));
+        let pixel = view.get_pixel(1, 1).unwrap();
+        assert_eq!((pixel.red, pixel.green), (3.0, 4.0));
+        assert!(view.get_pixel(4, 0).is_none());
+        assert_eq!(view.rows().count(), 2);
+        assert!(view.rows().all(|row| row.len() == 4));
+    }
+
+    #[test]
+    fn test_view_rows_are_premultiplied() {
+        let mut canvas = Canvas::from_wh(4, 4);
+        let color = LinSrgba::new(1.0, 0.5, 0.25, 0.5);
+        canvas.put_pixel(2, 3, color).unwrap();
+        let view = canvas.view(1, 2, 2, 2).unwrap();
+        let pixel = view.row(1).unwrap()[1];
+        assert_eq!((pixel.red, pixel.green, pixel.blue), (0.5, 0.25, 0.125));
+        assert_eq!(LinSrgba::from(pixel), color);
+        assert_eq!(view.get_pixel(1, 1), Some(color));
+    }
+
+    #[test]
+    fn test_nested_view_origin() {
+        let canvas = numbered_canvas(8, 6);
+        let view = canvas.view(1, 1, 6, 4).unwrap();
+        let inner = view.view(2, 1, 2, 2).unwrap();
+        assert_eq!(inner.origin(), (3, 2));
+        let pixel = inner.get_pixel(0, 0).unwrap();
+        assert_eq!((pixel.red, pixel.green), (3.0, 2.0));
+        assert!(view.view(5, 0, 2, 1).is_err());
+    }
+
+    #[test]
+    fn test_view_out_of_bounds() {
+        let canvas = numbered_canvas(8, 6);
+        assert!(matches!(
+            canvas.view(6, 0, 3, 1),
+            Err(Error::RegionOutOfBounds {
+                x: 6,
+                y: 0,
+                width: 3,
+                height: 1,
+                canvas_width: 8,
+                canvas_height: 6
+            })
+        ));
+        assert!(matches!(
+            canvas.view(usize::MAX, 0, 2, 1),
+            Err(Error::RegionOutOfBounds {
+                x: usize::MAX,
+                width: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_view_mut_writes_through() {
+        let mut canvas = Canvas::from_wh(5, 5);
+        let red = LinSrgba::new(1.0, 0.0, 0.0, 1.0);
+        {
+            let mut view = canvas.view_mut(1, 2, 3, 2).unwrap();
+            for row in view.rows_mut() {
+                row.fill(red.into());
+            }
+            assert!(view.put_pixel(3, 0, red).is_err());
+        }
+        assert_eq!(canvas.get_pixel(1, 2), Some(red));
+        assert_eq!(canvas.get_pixel(3, 3), Some(red));
+        assert_ne!(canvas.get_pixel(4, 3), Some(red));
+        assert_ne!(canvas.get_pixel(1, 4), Some(red));
+    }
+
+    #[test]
+    fn test_zero_width_view_rows() {
+        let mut canvas = numbered_canvas(4, 3);
+        let mut view = canvas.view_mut(2, 0, 0, 3).unwrap();
+        assert_eq!(view.rows().count(), 3);
+        assert_eq!(view.rows_mut().count(), 3);
+        assert!(view.rows_mut().all(|row| row.is_empty()));
+        // An empty region may sit past the last pixel
+        assert_eq!(canvas.view(4, 3, 0, 0).unwrap().rows().count(), 0);
+        // Rows of a narrower view skip the rest of each canvas row
+        let mut view = canvas.view_mut(1, 1, 2, 2).unwrap();
+        let firsts: Vec<_> = view
+            .rows_mut()
+            .map(|row| (row.len(), row[0].red, row[0].green))
+            .collect();
+        assert_eq!(firsts, vec![(2, 1.0, 1.0), (2, 1.0, 2.0)]);
+    }
+
+    #[test]
+    fn test_enumerate_pixels() {
+        let canvas = numbered_canvas(4, 3);
+        let view = canvas.view(1, 1, 2, 2).unwrap();
+        let coords: Vec<_> = view
+            .enumerate_pixels()
+            .map(|(x, y, color)| (x, y, color.red as usize, color.green as usize))
+            .collect();
+        assert_eq!(
+            coords,
+            vec![(0, 0, 1, 1), (1, 0, 2, 1), (0, 1, 1, 2), (1, 1, 2, 2)]
+        );
+    }
+}
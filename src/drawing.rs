@@ -0,0 +1,688 @@
+use std::ops::Range;
+
+use rayon::prelude::*;
+
+use crate::algorithms::antialias::{coverage_range, for_each_coverage_span, with_coverage};
+use crate::algorithms::boolean::{boolean_op, BooleanOp};
+use crate::algorithms::rasterizer::Rasterizer;
+use crate::algorithms::trace::{FillTracer, NoTrace};
+use crate::canvas::{BlendMode, Canvas, Paint, Pixel};
+use crate::error::Result;
+use crate::geometry::FillRule;
+use crate::geometry::{Affine2, Geometry, MultiPolygon, Point, Polygon, Transform};
+
+// Largest error when primitives are flattened for masking and occlusion
+const FLATTEN_TOLERANCE: f64 = 0.1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shape {
+    pub geometry: Geometry,
+    pub paint: Paint,
+}
+
+impl Shape {
+    /// `paint` is a `Paint` or a flat `LinSrgba<f64>` color.
+    pub fn new(geometry: impl Into<Geometry>, paint: impl Into<Paint>) -> Self {
+        Shape {
+            geometry: geometry.into(),
+            paint: paint.into(),
+        }
+    }
+
+    pub fn check_finite(&self) -> Result<()> {
+        self.geometry.check_finite()?;
+        self.paint.check_finite()
+    }
+}
+
+// Composites `paint` onto `pixels`, which start at column `x_start` of `row`, with its
+// alpha scaled by `coverage`.
+fn blend_paint(
+    mode: BlendMode,
+    paint: &Paint,
+    pixels: &mut [Pixel],
+    row: usize,
+    x_start: usize,
+    coverage: f32,
+) {
+    match paint {
+        Paint::Solid(color) => {
+            mode.blend_span(pixels, with_coverage(color.into_format(), coverage))
+        }
+        Paint::Gradient(gradient) => {
+            let colors = (x_start..).map(|x| {
+                let color = gradient.color_at(Point::new(x as f64, row as f64));
+                with_coverage(color.into_format(), coverage)
+            });
+            mode.blend_each(pixels, colors);
+        }
+    }
+}
+
+// An ordered stack of shapes, painted from first (bottom) to last (top).
+// Polygons, multi-polygons and paths are filled with `rule` and hard edges, primitives
+// with anti-aliased edges.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Drawing {
+    pub shapes: Vec<Shape>,
+    pub rule: FillRule,
+    pub blend: BlendMode,
+}
+
+impl Drawing {
+    pub fn new(rule: FillRule, blend: BlendMode) -> Self {
+        Drawing {
+            shapes: Vec::new(),
+            rule,
+            blend,
+        }
+    }
+
+    /// Paints all shapes on top of the current canvas content.
+    pub fn render(&self, canvas: &mut Canvas) -> Result<()> {
+        self.render_with(&mut Rasterizer::new(), canvas)
+    }
+
+    /// Like `render`, reusing the buffers of `rasterizer`.
+    pub fn render_with(&self, rasterizer: &mut Rasterizer, canvas: &mut Canvas) -> Result<()> {
+        self.render_traced(rasterizer, canvas, &mut NoTrace)
+    }
+
+    /// Like `render_with`, reporting every shape's fill to `tracer`.
+    pub fn render_traced(
+        &self,
+        rasterizer: &mut Rasterizer,
+        canvas: &mut Canvas,
+        tracer: &mut impl FillTracer,
+    ) -> Result<()> {
+        let (width, height) = (canvas.width(), canvas.height());
+        let pixels = canvas.pixels_mut();
+        for shape in &self.shapes {
+            shape.check_finite()?;
+            tracer.begin_polygon();
+            self.shape_spans(
+                rasterizer,
+                shape,
+                width,
+                height,
+                0..height,
+                |row, lo, hi, coverage| {
+                    tracer.span(row, lo, hi);
+                    let pixels = &mut pixels[row * width + lo..row * width + hi];
+                    blend_paint(self.blend, &shape.paint, pixels, row, lo, coverage);
+                },
+            );
+        }
+        Ok(())
+    }
+
+    // Calls `span(row, x_start, x_end, coverage)` for the pixels of `shape` in `rows`.
+    fn shape_spans(
+        &self,
+        rasterizer: &mut Rasterizer,
+        shape: &Shape,
+        width: usize,
+        height: usize,
+        rows: Range<usize>,
+        mut span: impl FnMut(usize, usize, usize, f32),
+    ) {
+        match (&shape.geometry, shape.geometry.as_primitive()) {
+            (_, Some(primitive)) => for_each_coverage_span(primitive, width, rows, span),
+            (Geometry::Polygon(polygon), None) => rasterizer.for_each_span_in_rows(
+                polygon,
+                width,
+                height,
+                rows,
+                self.rule,
+                |row, lo, hi| span(row, lo, hi, 1.0),
+            ),
+            (Geometry::MultiPolygon(multi), None) => rasterizer.for_each_span_of_rings(
+                multi.rings(),
+                width,
+                height,
+                rows,
+                self.rule,
+                |row, lo, hi| span(row, lo, hi, 1.0),
+            ),
+            (Geometry::Path(path), None) => rasterizer.for_each_span_of_rings(
+                path.rings(),
+                width,
+                height,
+                rows,
+                self.rule,
+                |row, lo, hi| span(row, lo, hi, 1.0),
+            ),
+            _ => {}
+        }
+    }
+
+    /// Like `render`, painting horizontal bands of the canvas in parallel. The result is
+    /// identical to `render`.
+    pub fn render_par(&self, canvas: &mut Canvas) -> Result<()> {
+        let bands = 4 * rayon::current_num_threads();
+        self.render_bands(canvas, canvas.height().div_ceil(bands))
+    }
+
+    /// Like `render_par`, with bands of `band_height` rows. Each band paints the shapes
+    /// reaching into it in z-order, scanning only its own rows.
+    pub fn render_bands(&self, canvas: &mut Canvas, band_height: usize) -> Result<()> {
+        for shape in &self.shapes {
+            shape.check_finite()?;
+        }
+        let band_height = band_height.max(1);
+        let (width, height) = (canvas.width(), canvas.height());
+        let bins = self.bin_by_band(height, band_height);
+        canvas
+            .par_bands_mut(band_height)
+            .for_each_init(Rasterizer::new, |rasterizer, mut band| {
+                let y0 = band.origin().1;
+                let rows = y0..y0 + band.height();
+                for &i in &bins[y0 / band_height] {
+                    let shape = &self.shapes[i];
+                    self.shape_spans(
+                        rasterizer,
+                        shape,
+                        width,
+                        height,
+                        rows.clone(),
+                        |row, lo, hi, c| {
+                            if let Some(pixels) = band.row_mut(row - y0) {
+                                let pixels = &mut pixels[lo..hi];
+                                blend_paint(self.blend, &shape.paint, pixels, row, lo, c);
+                            }
+                        },
+                    );
+                }
+            });
+        Ok(())
+    }
+
+    // Indices of the shapes reaching into each band of `band_height` rows, in z-order.
+    fn bin_by_band(&self, height: usize, band_height: usize) -> Vec<Vec<usize>> {
+        let mut bins = vec![Vec::new(); height.div_ceil(band_height)];
+        for (i, shape) in self.shapes.iter().enumerate() {
+            let Some(bbox) = shape.geometry.bounding_box() else {
+                continue;
+            };
+            // The rows the shape is scanned on
+            let rows = if shape.geometry.as_primitive().is_some() {
+                coverage_range(bbox.min.y, bbox.max.y, 0..height)
+            } else {
+                // `as` saturates, so rows above the canvas become 0
+                bbox.min.y.ceil() as usize..(bbox.max.y.ceil() as usize).min(height)
+            };
+            if !rows.is_empty() {
+                for bin in &mut bins[rows.start / band_height..rows.end.div_ceil(band_height)] {
+                    bin.push(i);
+                }
+            }
+        }
+        bins
+    }
+
+    /// Index of the topmost shape covering `p` under the drawing's fill rule.
+    /// Pixel `(x, y)` is sampled at `Point::new(x as f64, y as f64)`, as in rendering;
+    /// primitives cover the pixels where their coverage is at least one half.
+    pub fn topmost_at(&self, p: Point<f64>) -> Option<usize> {
+        self.shapes.iter().rposition(|shape| {
+            shape
+                .geometry
+                .bounding_box()
+                .is_some_and(|bbox| bbox.contains(p))
+                && shape.geometry.contains(p, self.rule)
+        })
+    }
+
+    /// Maps every shape and its paint through `t`.
+    pub fn transform(&mut self, t: &Affine2) {
+        for shape in &mut self.shapes {
+            shape.geometry = shape.geometry.transform(t);
+            shape.paint = shape.paint.transform(t);
+        }
+    }
+
+    /// Clips every shape to `stencil`. Shapes split into several pieces keep their
+    /// paint and z-order; shapes outside the stencil are removed. Every shape is
+    /// clipped as filled under the drawing's rule. Primitives are flattened, polygons
+    /// and primitives become one polygon per piece, and multi-polygons and paths become
+    /// one multi-polygon with their holes.
+    pub fn mask(&mut self, stencil: &Polygon<f64>) {
+        let rule = self.rule;
+        self.shapes = self
+            .shapes
+            .iter()
+            .flat_map(|shape| {
+                let rings = shape.geometry.to_rings(FLATTEN_TOLERANCE);
+                let stencil = std::slice::from_ref(stencil);
+                // Self-intersecting rings are clipped as they are filled, under `rule`
+                let clipped = boolean_op(&rings, stencil, BooleanOp::Intersection, rule);
+                let pieces: Vec<Geometry> = match &shape.geometry {
+                    Geometry::MultiPolygon(_) | Geometry::Path(_) => (!clipped.is_empty())
+                        .then(|| clipped.into())
+                        .into_iter()
+                        .collect(),
+                    _ => clipped
+                        .polygons
+                        .into_iter()
+                        .map(|part| {
+                            if part.holes.is_empty() {
+                                part.exterior.into()
+                            } else {
+                                part.into()
+                            }
+                        })
+                        .collect(),
+                };
+                pieces
+                    .into_iter()
+                    .map(|geometry| Shape::new(geometry, shape.paint.clone()))
+            })
+            .collect();
+    }
+
+    // Whether a shape hides everything below it.
+    fn is_opaque(&self, shape: &Shape) -> bool {
+        self.blend == BlendMode::Normal && shape.paint.is_opaque()
+    }
+
+    /// Area of each shape not hidden by opaque shapes above it. Primitives are measured
+    /// on their flattened outline.
+    pub fn visible_areas(&self) -> Vec<f64> {
+        let mut covered = MultiPolygon::default();
+        let mut areas: Vec<f64> = self
+            .shapes
+            .iter()
+            .rev()
+            .map(|shape| {
+                let own = shape.geometry.to_rings(FLATTEN_TOLERANCE);
+                let occluders: Vec<Polygon<f64>> = covered.rings().cloned().collect();
+                let visible = boolean_op(&own, &occluders, BooleanOp::Difference, self.rule);
+                if self.is_opaque(shape) {
+                    covered = boolean_op(&occluders, &own, BooleanOp::Union, self.rule);
+                }
+                visible.area()
+            })
+            .collect();
+        areas.reverse();
+        areas
+    }
+
+    /// Removes shapes with less than `min_area` visible. Returns how many were removed.
+    pub fn prune_hidden(&mut self, min_area: f64) -> usize {
+        let areas = self.visible_areas();
+        let before = self.shapes.len();
+        let mut areas = areas.iter();
+        self.shapes.retain(|_| *areas.next().unwrap() >= min_area);
+        before - self.shapes.len()
+    }
+
+    /// Scales every shape about the origin, e.g. to move a drawing between resolutions.
+    pub fn scale(&mut self, sx: f64, sy: f64) {
+        self.transform(&Affine2::scale(sx, sy));
+    }
+}
+
+#[cfg(test)]
+mod drawing_tests {
+    use super::*;
+    use crate::algorithms::trace::FillStats;
+    use crate::canvas::{CanvasSpec, Gradient, GradientStop};
+    use crate::geometry::{Circle, Ellipse, Path, PolygonWithHoles, Primitive, RoundedRect};
+    use crate::test_utils::square;
+    use palette::rgb::LinSrgba;
+
+    #[test]
+    fn test_render_respects_z_order() {
+        let red = LinSrgba::new(1.0, 0.0, 0.0, 1.0);
+        let blue = LinSrgba::new(0.0, 0.0, 1.0, 1.0);
+        let mut drawing = Drawing::new(FillRule::NonZero, BlendMode::Normal);
+        drawing.shapes.push(Shape::new(square(0.0, 0.0, 6.0), red));
+        drawing.shapes.push(Shape::new(square(3.0, 3.0, 6.0), blue));
+        let mut canvas = Canvas::white(CanvasSpec::new(10, 10));
+        drawing.render(&mut canvas).unwrap();
+        assert_eq!(canvas.get_pixel(1, 1).unwrap(), red.into_format());
+        assert_eq!(canvas.get_pixel(4, 4).unwrap(), blue.into_format());
+        assert_eq!(
+            canvas.get_pixel(9, 0).unwrap(),
+            LinSrgba::new(1.0, 1.0, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_scale() {
+        let mut drawing = Drawing::default();
+        drawing.shapes.push(Shape::new(
+            square(1.0, 2.0, 3.0),
+            LinSrgba::new(0.0, 0.0, 0.0, 1.0),
+        ));
+        drawing.scale(2.0, 0.5);
+        let expected = Polygon::from_vec(vec![2.0, 1.0, 8.0, 1.0, 8.0, 2.5, 2.0, 2.5]).unwrap();
+        assert_eq!(drawing.shapes[0].geometry, Geometry::from(expected));
+    }
+
+    #[test]
+    fn test_topmost_at() {
+        let black = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        let mut drawing = Drawing::default();
+        drawing
+            .shapes
+            .push(Shape::new(square(0.0, 0.0, 6.0), black));
+        drawing
+            .shapes
+            .push(Shape::new(square(3.0, 3.0, 6.0), black));
+        assert_eq!(drawing.topmost_at(Point::new(1.0, 1.0)), Some(0));
+        assert_eq!(drawing.topmost_at(Point::new(4.0, 4.0)), Some(1));
+        assert_eq!(drawing.topmost_at(Point::new(9.5, 1.0)), None);
+    }
+
+    #[test]
+    fn test_topmost_at_matches_rendering() {
+        let black = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        let star = Polygon::from_vec(vec![0, 0, 20, 0, 3, 15, 13, 3, 8, 3, 18, 15]).unwrap();
+        let star = Polygon {
+            vertices: star
+                .vertices
+                .iter()
+                .map(|p| Point::new(p.x as f64, p.y as f64))
+                .collect(),
+        };
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            let mut drawing = Drawing::new(rule, BlendMode::Normal);
+            drawing.shapes.push(Shape::new(star.clone(), black));
+            let mut canvas = Canvas::white(CanvasSpec::new(30, 20));
+            drawing.render(&mut canvas).unwrap();
+            for (x, y, pixel) in canvas.view(0, 0, 30, 20).unwrap().enumerate_pixels() {
+                let hit = drawing.topmost_at(Point::new(x as f64, y as f64));
+                assert_eq!(hit.is_some(), pixel.red == 0.0, "pixel ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mask() {
+        let black = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        let mut drawing = Drawing::default();
+        drawing
+            .shapes
+            .push(Shape::new(square(0.0, 0.0, 4.0), black));
+        drawing
+            .shapes
+            .push(Shape::new(square(10.0, 10.0, 2.0), black));
+        drawing.mask(&square(2.0, 2.0, 4.0));
+        assert_eq!(drawing.shapes.len(), 1);
+        assert!((drawing.shapes[0].geometry.area() - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_mask_self_intersecting() {
+        let black = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        // A bow-tie whose lobes meet at (2, 2) with opposite windings
+        let bow_tie = Polygon::from_vec(vec![0.0, 0.0, 4.0, 4.0, 4.0, 0.0, 0.0, 4.0]).unwrap();
+        // A pentagram, whose center winds twice
+        let star = Polygon::from_vec(vec![
+            10.0, 0.0, 12.94, 9.05, 5.24, 3.45, 14.76, 3.45, 7.06, 9.05,
+        ])
+        .unwrap();
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            let mut drawing = Drawing::new(rule, BlendMode::Normal);
+            drawing.shapes.push(Shape::new(bow_tie.clone(), black));
+            // Cuts the left lobe at y = 3 and the right one at x = 3
+            drawing.mask(&square(0.0, 0.0, 3.0));
+            assert_eq!(drawing.shapes.len(), 2, "{:?}", rule);
+            let area: f64 = drawing.shapes.iter().map(|s| s.geometry.area()).sum();
+            assert!((area - 4.5).abs() < 1e-9, "{:?} {}", rule, area);
+            assert!(drawing.topmost_at(Point::new(1.0, 2.0)).is_some());
+            assert!(drawing.topmost_at(Point::new(2.5, 2.0)).is_some());
+            assert_eq!(drawing.topmost_at(Point::new(2.0, 1.0)), None);
+
+            let mut drawing = Drawing::new(rule, BlendMode::Normal);
+            drawing.shapes.push(Shape::new(star.clone(), black));
+            drawing.mask(&square(0.0, 0.0, 20.0));
+            let center = drawing.topmost_at(Point::new(10.0, 4.5));
+            assert_eq!(center.is_some(), rule == FillRule::NonZero, "{:?}", rule);
+            assert!(drawing.topmost_at(Point::new(10.0, 1.0)).is_some());
+        }
+    }
+
+    #[test]
+    fn test_visible_areas_and_pruning() {
+        let opaque = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        let translucent = LinSrgba::new(0.0, 0.0, 0.0, 0.5);
+        let mut drawing = Drawing::new(FillRule::NonZero, BlendMode::Normal);
+        drawing
+            .shapes
+            .push(Shape::new(square(1.0, 1.0, 2.0), opaque));
+        drawing
+            .shapes
+            .push(Shape::new(square(2.0, 0.0, 4.0), opaque));
+        drawing
+            .shapes
+            .push(Shape::new(square(0.0, 0.0, 4.0), translucent));
+        drawing
+            .shapes
+            .push(Shape::new(square(0.0, 0.0, 2.0), opaque));
+        let areas = drawing.visible_areas();
+        let expected = [1.0, 16.0, 12.0, 4.0];
+        for (area, expected) in areas.iter().zip(expected) {
+            assert!((area - expected).abs() < 1e-9, "{:?}", areas);
+        }
+        assert_eq!(drawing.prune_hidden(2.0), 1);
+        assert_eq!(drawing.shapes.len(), 3);
+        assert_eq!(
+            drawing.shapes[0].geometry,
+            Geometry::from(square(2.0, 0.0, 4.0))
+        );
+    }
+
+    #[test]
+    fn test_render_traced() {
+        let black = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        let mut drawing = Drawing::default();
+        drawing
+            .shapes
+            .push(Shape::new(square(0.0, 0.0, 4.0), black));
+        drawing
+            .shapes
+            .push(Shape::new(square(2.0, 2.0, 4.0), black));
+        let mut canvas = Canvas::white(CanvasSpec::new(10, 10));
+        let mut stats = FillStats::default();
+        drawing
+            .render_traced(&mut Rasterizer::new(), &mut canvas, &mut stats)
+            .unwrap();
+        assert_eq!(
+            (stats.polygons, stats.rows, stats.spans, stats.pixels),
+            (2, 8, 8, 32)
+        );
+        assert!(stats
+            .per_polygon
+            .iter()
+            .all(|p| (p.rows, p.spans, p.pixels) == (4, 4, 16)));
+    }
+
+    #[test]
+    fn test_render_bands_matches_render() {
+        let mut rng = crate::utils::Rng::new(11);
+        for blend in [BlendMode::Normal, BlendMode::Multiply] {
+            let mut drawing = Drawing::new(FillRule::EvenOdd, blend);
+            for _ in 0..40 {
+                let mut poly = Polygon::new();
+                for _ in 0..3 + rng.range_f64(0.0, 6.0) as usize {
+                    poly.add_point(Point::new(
+                        rng.range_f64(-10.0, 50.0),
+                        rng.range_f64(-10.0, 40.0),
+                    ));
+                }
+                let color = LinSrgba::new(
+                    rng.range_f64(0.0, 1.0),
+                    rng.range_f64(0.0, 1.0),
+                    rng.range_f64(0.0, 1.0),
+                    0.6,
+                );
+                drawing.shapes.push(Shape::new(poly, color));
+                let center = Point::new(rng.range_f64(-5.0, 45.0), rng.range_f64(-5.0, 35.0));
+                let (rx, ry) = (rng.range_f64(0.5, 10.0), rng.range_f64(0.5, 10.0));
+                let ellipse = Ellipse::new(center, rx, ry, rng.range_f64(0.0, 3.0));
+                let stops = vec![
+                    GradientStop::new(0.0, color),
+                    GradientStop::new(1.0, LinSrgba::new(1.0, 1.0, 0.0, 0.3)),
+                ];
+                let gradient = Gradient::radial(center, rx, stops);
+                drawing.shapes.push(Shape::new(ellipse, gradient));
+            }
+            let mut expected = Canvas::white(CanvasSpec::new(40, 30));
+            drawing.render(&mut expected).unwrap();
+            for band_height in [0, 1, 4, 7, 30, 100] {
+                let mut actual = Canvas::white(CanvasSpec::new(40, 30));
+                drawing.render_bands(&mut actual, band_height).unwrap();
+                assert_eq!(
+                    expected.pixels(),
+                    actual.pixels(),
+                    "bands of {}",
+                    band_height
+                );
+            }
+            let mut actual = Canvas::white(CanvasSpec::new(40, 30));
+            drawing.render_par(&mut actual).unwrap();
+            assert_eq!(expected.pixels(), actual.pixels());
+        }
+    }
+
+    #[test]
+    fn test_render_primitives() {
+        let black = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        let mut drawing = Drawing::default();
+        drawing
+            .shapes
+            .push(Shape::new(Circle::new(Point::new(10.0, 10.0), 5.0), black));
+        let rect = RoundedRect::new(Point::new(30.0, 10.0), 6.0, 4.0, 2.0, 0.5);
+        drawing.shapes.push(Shape::new(rect, black));
+        let mut canvas = Canvas::white(CanvasSpec::new(40, 20));
+        drawing.render(&mut canvas).unwrap();
+        // Black over white, so the coverage shows in every channel
+        let ink = |x, y| 1.0 - canvas.get_pixel(x, y).unwrap().red;
+        assert_eq!(ink(10, 10), 1.0);
+        assert_eq!(ink(30, 10), 1.0);
+        assert_eq!(ink(0, 0), 0.0);
+        // The outline is half covered
+        assert!((ink(15, 10) - 0.5).abs() < 1e-6);
+        assert!((ink(10, 5) - 0.5).abs() < 1e-6);
+        assert_eq!(drawing.topmost_at(Point::new(30.0, 10.0)), Some(1));
+        assert_eq!(drawing.topmost_at(Point::new(16.0, 10.0)), None);
+
+        let total: f32 = canvas.pixels().iter().map(|p| 1.0 - p.red).sum();
+        let area = drawing.shapes[0].geometry.area() + rect.area();
+        assert!(
+            (total as f64 - area).abs() < 0.01 * area,
+            "{} vs {}",
+            total,
+            area
+        );
+
+        drawing.shapes[0].geometry = Circle::new(Point::new(f64::NAN, 0.0), 1.0).into();
+        assert!(drawing.render(&mut canvas).is_err());
+    }
+
+    #[test]
+    fn test_render_gradient() {
+        let stops = vec![
+            GradientStop::new(0.0, LinSrgba::new(0.0, 0.0, 0.0, 1.0)),
+            GradientStop::new(1.0, LinSrgba::new(1.0, 0.0, 0.0, 1.0)),
+        ];
+        let gradient = Gradient::linear(Point::new(0.0, 0.0), Point::new(8.0, 0.0), stops);
+        let mut drawing = Drawing::default();
+        drawing
+            .shapes
+            .push(Shape::new(square(0.0, 0.0, 10.0), gradient));
+        let mut canvas = Canvas::white(CanvasSpec::new(10, 10));
+        drawing.render(&mut canvas).unwrap();
+        for (x, red) in [(0, 0.0), (2, 0.25), (4, 0.5), (9, 1.0)] {
+            let pixel = canvas.get_pixel(x, 5).unwrap();
+            assert_eq!((pixel.red, pixel.green, pixel.alpha), (red, 0.0, 1.0));
+        }
+        assert_eq!(drawing.visible_areas(), vec![100.0]);
+
+        // The gradient moves with the shape
+        drawing.scale(0.5, 0.5);
+        let mut canvas = Canvas::white(CanvasSpec::new(10, 10));
+        drawing.render(&mut canvas).unwrap();
+        assert_eq!(canvas.get_pixel(2, 2).unwrap().red, 0.5);
+    }
+
+    #[test]
+    fn test_shape_with_holes() {
+        // The hole runs the same way as the exterior until `PolygonWithHoles::new`
+        // reverses it
+        let frame = PolygonWithHoles::new(square(0.0, 0.0, 10.0), vec![square(3.0, 3.0, 4.0)]);
+        let black = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        let mut drawing = Drawing::default();
+        drawing.shapes.push(Shape::new(frame, black));
+        let mut canvas = Canvas::white(CanvasSpec::new(12, 12));
+        drawing.render(&mut canvas).unwrap();
+        assert_eq!(canvas.get_pixel(1, 1).unwrap().red, 0.0);
+        assert_eq!(canvas.get_pixel(5, 5).unwrap().red, 1.0);
+        assert_eq!(drawing.topmost_at(Point::new(5.0, 5.0)), None);
+        assert_eq!(drawing.topmost_at(Point::new(1.0, 5.0)), Some(0));
+        assert_eq!(drawing.visible_areas(), vec![84.0]);
+
+        let mut banded = Canvas::white(CanvasSpec::new(12, 12));
+        drawing.render_bands(&mut banded, 5).unwrap();
+        assert_eq!(canvas.pixels(), banded.pixels());
+
+        // Masking keeps the hole
+        drawing.mask(&square(0.0, 0.0, 5.0));
+        assert_eq!(drawing.shapes.len(), 1);
+        assert!((drawing.shapes[0].geometry.area() - 21.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_path_shape() {
+        // A square with a square hole running the other way, the top edge bulging out
+        // as a curve
+        let hole = Path::from(&square(3.0, 3.0, 4.0));
+        let mut outer = Path::new();
+        outer
+            .move_to(Point::new(0.0, 10.0))
+            .line_to(Point::new(10.0, 10.0))
+            .line_to(Point::new(10.0, 0.0))
+            .cubic_to(
+                Point::new(7.0, -3.0),
+                Point::new(3.0, -3.0),
+                Point::new(0.0, 0.0),
+            )
+            .close();
+        outer.extend(hole.segments().iter().copied());
+        let black = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        let mut drawing = Drawing::default();
+        drawing.shapes.push(Shape::new(outer.clone(), black));
+        let mut canvas = Canvas::white(CanvasSpec::new(12, 12));
+        drawing.render(&mut canvas).unwrap();
+
+        let mut expected = Canvas::white(CanvasSpec::new(12, 12));
+        Rasterizer::new()
+            .fill_rings(
+                &mut expected,
+                outer.rings(),
+                black,
+                FillRule::NonZero,
+                BlendMode::Normal,
+            )
+            .unwrap();
+        assert_eq!(canvas.pixels(), expected.pixels());
+        assert_eq!(canvas.get_pixel(5, 5).unwrap().red, 1.0);
+        assert_eq!(canvas.get_pixel(5, 1).unwrap().red, 0.0);
+        assert_eq!(drawing.topmost_at(Point::new(5.0, 5.0)), None);
+        assert_eq!(drawing.topmost_at(Point::new(5.0, 0.0)), Some(0));
+        // The bulge adds half of its 10 x 3 control box
+        let area = drawing.shapes[0].geometry.area();
+        assert!((area - (100.0 - 16.0 + 15.0)).abs() < 0.5, "{}", area);
+
+        let mut banded = Canvas::white(CanvasSpec::new(12, 12));
+        drawing.render_bands(&mut banded, 5).unwrap();
+        assert_eq!(canvas.pixels(), banded.pixels());
+
+        // Masking keeps the hole
+        drawing.mask(&square(0.0, 1.0, 5.0));
+        assert_eq!(drawing.shapes.len(), 1);
+        assert!((drawing.shapes[0].geometry.area() - 19.0).abs() < 1e-9);
+    }
+}
@@ -1,17 +1,798 @@
+use std::f64::consts::PI;
 use std::path::Path;
 
-use crate::canvas::{Canvas, CanvasSpec};
+use palette::rgb::LinSrgba;
+
+use super::schedule::Schedule;
+use crate::algorithms::similarity::{ImageSimilarity, Mse};
+use crate::canvas::{
+    BlendMode, Canvas, CanvasSpec, Gradient, GradientShape, GradientStop, Paint, ResizeFilter,
+};
+use crate::drawing::{Drawing, Shape};
+use crate::error::Result;
+use crate::geometry::FillRule;
+use crate::geometry::{Circle, Ellipse, Geometry, Point, Polygon, RoundedRect};
+use crate::utils::Rng;
+
+/// The kind of shape the engine adds to the drawing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShapeKind {
+    #[default]
+    Triangle,
+    Circle,
+    Ellipse,
+    RoundedRect,
+    // A closed path of cubic Bézier curves
+    Blob,
+}
+
+/// How the engine paints the shapes it adds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PaintKind {
+    #[default]
+    Solid,
+    LinearGradient,
+    RadialGradient,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EngineOptions {
+    pub max_shapes: usize,
+    // Candidates with a shape smaller than this many square pixels are rejected.
+    pub min_shape_area: f64,
+    pub seed: u64,
+    // If set, `run` ends every stage with `simplify_pass` at this tolerance.
+    pub simplify_tolerance: Option<f64>,
+    // Keeps every shape convex by replacing mutated polygons with their convex hull.
+    pub convex_only: bool,
+    // Renders candidates in parallel bands with `Drawing::render_par`. The result is
+    // the same either way.
+    pub parallel_render: bool,
+    pub shape_kind: ShapeKind,
+    pub paint_kind: PaintKind,
+}
+
+impl Default for EngineOptions {
+    fn default() -> Self {
+        EngineOptions {
+            max_shapes: 50,
+            min_shape_area: 1.0,
+            seed: 0,
+            simplify_tolerance: None,
+            convex_only: false,
+            parallel_render: true,
+            shape_kind: ShapeKind::default(),
+            paint_kind: PaintKind::default(),
+        }
+    }
+}
 
 pub struct Engine {
+    // The reference at the full target resolution.
+    target: Canvas,
+    // The reference at the current working resolution.
+    reference: Canvas,
+    background: LinSrgba<f32>,
+    drawing: Drawing,
     canvas: Canvas,
     prev_score: f64,
+    metric: Box<dyn ImageSimilarity>,
+    options: EngineOptions,
+    rng: Rng,
 }
 
 impl Engine {
-    pub fn new(spec: CanvasSpec, image_path: &Path) -> Option<Self> {
-        Some(Self {
-            canvas: Canvas::from_spec(spec),
-            prev_score: 0f64,
+    pub fn new(spec: CanvasSpec, image_path: &Path) -> Result<Self> {
+        let reference = Canvas::from_srgb_image(image::open(image_path)?.into_rgba32f());
+        Self::from_reference(spec, &reference, EngineOptions::default())
+    }
+
+    /// Starts an empty drawing over the reference's mean color, with the reference
+    /// resized to `spec`.
+    pub fn from_reference(
+        spec: CanvasSpec,
+        reference: &Canvas,
+        options: EngineOptions,
+    ) -> Result<Self> {
+        let target = reference.resize(spec, ResizeFilter::Bilinear);
+        let background = target.mean_color();
+        let metric: Box<dyn ImageSimilarity> = Box::new(Mse);
+        let canvas = Canvas::from_color(spec, background);
+        let prev_score = metric.distance(&canvas, &target)?;
+        Ok(Self {
+            reference: target.clone(),
+            target,
+            background,
+            drawing: Drawing::new(FillRule::NonZero, BlendMode::Normal),
+            canvas,
+            prev_score,
+            metric,
+            options,
+            rng: Rng::new(options.seed),
         })
     }
+
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
+    }
+
+    pub fn reference(&self) -> &Canvas {
+        &self.reference
+    }
+
+    pub fn drawing(&self) -> &Drawing {
+        &self.drawing
+    }
+
+    /// The current working resolution.
+    pub fn spec(&self) -> CanvasSpec {
+        self.reference.get_spec()
+    }
+
+    pub fn score(&self) -> f64 {
+        self.prev_score
+    }
+
+    pub fn set_metric(&mut self, metric: Box<dyn ImageSimilarity>) -> Result<()> {
+        self.metric = metric;
+        self.prev_score = self.metric.distance(&self.canvas, &self.reference)?;
+        Ok(())
+    }
+
+    fn render(&self, drawing: &Drawing) -> Result<Canvas> {
+        let mut canvas = Canvas::from_color(self.spec(), self.background);
+        if self.options.parallel_render {
+            drawing.render_par(&mut canvas)?;
+        } else {
+            drawing.render(&mut canvas)?;
+        }
+        Ok(canvas)
+    }
+
+    /// Proposes one random mutation of the drawing and keeps it if the score improves.
+    /// Returns whether the mutation was accepted.
+    pub fn step(&mut self) -> Result<bool> {
+        let (candidate, changed) = self.mutate();
+        if let Some(index) = changed {
+            if candidate.shapes[index].geometry.area() < self.options.min_shape_area {
+                return Ok(false);
+            }
+        }
+        let canvas = self.render(&candidate)?;
+        let score = self.metric.distance(&canvas, &self.reference)?;
+        if score < self.prev_score {
+            self.drawing = candidate;
+            self.canvas = canvas;
+            self.prev_score = score;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Moves the optimization to another resolution. The drawing is scaled so it
+    /// covers the same part of the image, shapes that shrink below `min_shape_area`
+    /// are dropped, and the score is recomputed.
+    pub fn set_resolution(&mut self, spec: CanvasSpec) -> Result<()> {
+        let current = self.spec();
+        if current == spec {
+            return Ok(());
+        }
+        self.drawing.scale(
+            spec.width as f64 / current.width.max(1) as f64,
+            spec.height as f64 / current.height.max(1) as f64,
+        );
+        let min_area = self.options.min_shape_area;
+        self.drawing
+            .shapes
+            .retain(|shape| shape.geometry.area() >= min_area);
+        self.reference = self.target.resize(spec, ResizeFilter::Bilinear);
+        self.canvas = self.render(&self.drawing)?;
+        self.prev_score = self.metric.distance(&self.canvas, &self.reference)?;
+        Ok(())
+    }
+
+    /// Runs every stage of the schedule in order. The engine is left at the
+    /// resolution of the last stage.
+    pub fn run(&mut self, schedule: &Schedule) -> Result<()> {
+        for stage in &schedule.stages {
+            self.set_resolution(stage.spec)?;
+            for _ in 0..stage.steps {
+                self.step()?;
+            }
+            if let Some(tolerance) = self.options.simplify_tolerance {
+                self.simplify_pass(tolerance)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Simplifies each polygon in turn, keeping the result whenever the score does not
+    /// get worse. Returns the number of vertices removed.
+    pub fn simplify_pass(&mut self, tolerance: f64) -> Result<usize> {
+        let mut removed = 0;
+        for index in 0..self.drawing.shapes.len() {
+            let Some(polygon) = self.drawing.shapes[index].geometry.as_polygon() else {
+                continue;
+            };
+            let simplified = polygon.dedup_vertices(tolerance).simplify_rdp(tolerance);
+            let fewer = polygon.vertices.len() - simplified.vertices.len();
+            if fewer == 0
+                || simplified.vertices.len() < Polygon::<f64>::MIN_VERTICES
+                || simplified.area() < self.options.min_shape_area
+            {
+                continue;
+            }
+            let mut candidate = self.drawing.clone();
+            candidate.shapes[index].geometry = Geometry::Polygon(simplified);
+            let canvas = self.render(&candidate)?;
+            let score = self.metric.distance(&canvas, &self.reference)?;
+            if score <= self.prev_score {
+                self.drawing = candidate;
+                self.canvas = canvas;
+                self.prev_score = score;
+                removed += fewer;
+            }
+        }
+        Ok(removed)
+    }
+
+    // Returns the mutated drawing and the index of the shape added or changed, if any.
+    fn mutate(&mut self) -> (Drawing, Option<usize>) {
+        let mut drawing = self.drawing.clone();
+        let len = drawing.shapes.len();
+        let can_add = len < self.options.max_shapes;
+        if len == 0 || (can_add && self.rng.chance(0.2)) {
+            let shape = self.random_shape();
+            drawing.shapes.push(shape);
+            return (drawing, Some(len));
+        }
+
+        let index = self.rng.below(len);
+        let spec = self.spec();
+        let reach = 0.1 * spec.width.max(spec.height) as f64;
+        let roll = self.rng.next_f64();
+        if roll < 0.05 {
+            drawing.shapes.remove(index);
+            return (drawing, None);
+        } else if roll < 0.6 {
+            self.mutate_geometry(&mut drawing.shapes[index].geometry, reach);
+        } else {
+            self.mutate_paint(&mut drawing.shapes[index].paint, reach);
+        }
+        (drawing, Some(index))
+    }
+
+    fn jitter_color(&mut self, c: LinSrgba<f64>) -> LinSrgba<f64> {
+        let mut jitter = |v: f64| (v + self.rng.range_f64(-0.1, 0.1)).clamp(0.0, 1.0);
+        LinSrgba::new(
+            jitter(c.red),
+            jitter(c.green),
+            jitter(c.blue),
+            jitter(c.alpha),
+        )
+    }
+
+    // Changes the color, or for gradients one stop color or one endpoint.
+    fn mutate_paint(&mut self, paint: &mut Paint, reach: f64) {
+        let gradient = match paint {
+            Paint::Solid(color) => {
+                *color = self.jitter_color(*color);
+                return;
+            }
+            Paint::Gradient(gradient) => gradient,
+        };
+        if gradient.stops.is_empty() || self.rng.chance(0.5) {
+            let offset = Point::new(
+                self.rng.range_f64(-reach, reach),
+                self.rng.range_f64(-reach, reach),
+            );
+            match &mut gradient.shape {
+                GradientShape::Linear { start, end } => {
+                    let moved = if self.rng.chance(0.5) { start } else { end };
+                    *moved = *moved + offset;
+                }
+                GradientShape::Radial { center, radius } => {
+                    if self.rng.chance(0.5) {
+                        *center = *center + offset;
+                    } else {
+                        *radius = (*radius + offset.x).max(0.5);
+                    }
+                }
+                GradientShape::Elliptical { center, u, v } => match self.rng.below(3) {
+                    0 => *center = *center + offset,
+                    1 => *u = *u + offset,
+                    _ => *v = *v + offset,
+                },
+            }
+        } else {
+            let stop = self.rng.below(gradient.stops.len());
+            gradient.stops[stop].color = self.jitter_color(gradient.stops[stop].color);
+        }
+    }
+
+    // Moves one vertex of a polygon or multi-polygon, one point of a path, or one
+    // parameter of a primitive, by up to `reach`.
+    fn mutate_geometry(&mut self, geometry: &mut Geometry, reach: f64) {
+        let offset = Point::new(
+            self.rng.range_f64(-reach, reach),
+            self.rng.range_f64(-reach, reach),
+        );
+        let delta = self.rng.range_f64(-reach, reach) / 2.0;
+        let turn = self.rng.range_f64(-0.5, 0.5);
+        let nudge = |v: f64| (v + delta).max(0.5);
+        match geometry {
+            Geometry::Polygon(polygon) => {
+                let vertex = self.rng.below(polygon.vertices.len());
+                let mut moved = polygon.clone();
+                moved.vertices[vertex] = moved.vertices[vertex] + offset;
+                if self.options.convex_only {
+                    moved = moved.convex_hull();
+                    // Collinear vertices leave a hull of fewer than three, so skip the move
+                    if moved.vertices.len() < Polygon::<f64>::MIN_VERTICES {
+                        return;
+                    }
+                }
+                *polygon = moved;
+            }
+            Geometry::MultiPolygon(multi) => {
+                // An empty ring has no vertex to move
+                let mut rings: Vec<&mut Polygon<f64>> = multi
+                    .rings_mut()
+                    .filter(|ring| !ring.vertices.is_empty())
+                    .collect();
+                if !rings.is_empty() {
+                    let index = self.rng.below(rings.len());
+                    let ring = &mut rings[index];
+                    let vertex = self.rng.below(ring.vertices.len());
+                    ring.vertices[vertex] = ring.vertices[vertex] + offset;
+                }
+                // The move may have turned a ring around
+                multi.normalize();
+            }
+            Geometry::Path(path) => {
+                let count = path.points().count();
+                if count > 0 {
+                    let index = self.rng.below(count);
+                    if let Some(p) = path.points_mut().nth(index) {
+                        *p = *p + offset;
+                    }
+                }
+            }
+            Geometry::Circle(circle) => match self.rng.below(2) {
+                0 => circle.center = circle.center + offset,
+                _ => circle.radius = nudge(circle.radius),
+            },
+            Geometry::Ellipse(ellipse) => match self.rng.below(4) {
+                0 => ellipse.center = ellipse.center + offset,
+                1 => ellipse.rx = nudge(ellipse.rx),
+                2 => ellipse.ry = nudge(ellipse.ry),
+                _ => ellipse.angle += turn,
+            },
+            Geometry::RoundedRect(rect) => match self.rng.below(5) {
+                0 => rect.center = rect.center + offset,
+                1 => rect.half_width = nudge(rect.half_width),
+                2 => rect.half_height = nudge(rect.half_height),
+                3 => rect.radius = nudge(rect.radius),
+                _ => rect.angle += turn,
+            },
+        }
+    }
+
+    // A small shape of the configured kind, colored like the reference under its center.
+    fn random_shape(&mut self) -> Shape {
+        let spec = self.spec();
+        let (w, h) = (spec.width as f64, spec.height as f64);
+        let center = Point::new(self.rng.range_f64(0.0, w), self.rng.range_f64(0.0, h));
+        let reach = 0.2 * w.max(h);
+        let mut size = || self.rng.range_f64(0.5, reach.max(1.0));
+        let geometry: Geometry = match self.options.shape_kind {
+            ShapeKind::Triangle => {
+                let mut polygon = Polygon::new();
+                for _ in 0..3 {
+                    let offset = Point::new(
+                        self.rng.range_f64(-reach, reach),
+                        self.rng.range_f64(-reach, reach),
+                    );
+                    polygon.add_point(center + offset);
+                }
+                polygon.into()
+            }
+            ShapeKind::Circle => Circle::new(center, size()).into(),
+            ShapeKind::Ellipse => {
+                let (rx, ry) = (size(), size());
+                Ellipse::new(center, rx, ry, self.rng.range_f64(0.0, PI)).into()
+            }
+            ShapeKind::RoundedRect => {
+                let (hw, hh, r) = (size(), size(), size() / 2.0);
+                RoundedRect::new(center, hw, hh, r, self.rng.range_f64(0.0, PI)).into()
+            }
+            ShapeKind::Blob => {
+                // Three points around the center, joined by curves with random controls
+                let mut jitter = || {
+                    center
+                        + Point::new(
+                            self.rng.range_f64(-reach, reach),
+                            self.rng.range_f64(-reach, reach),
+                        )
+                };
+                let points: Vec<Point<f64>> = (0..9).map(|_| jitter()).collect();
+                let mut path = crate::geometry::Path::new();
+                path.move_to(points[0]);
+                for k in 0..3 {
+                    let end = points[(3 * k + 3) % 9];
+                    path.cubic_to(points[3 * k + 1], points[3 * k + 2], end);
+                }
+                path.close();
+                path.into()
+            }
+        };
+        let at = match &geometry {
+            Geometry::Polygon(polygon) => polygon.centroid().unwrap_or(center),
+            _ => center,
+        };
+        // Gradients span about the size of the shape, through its center
+        let angle = self.rng.range_f64(0.0, 2.0 * PI);
+        let half = Point::new(angle.cos(), angle.sin()) * (reach / 2.0);
+        let paint: Paint = match self.options.paint_kind {
+            PaintKind::Solid => self.sample(at).into(),
+            PaintKind::LinearGradient => {
+                let (start, end) = (at - half, at + half);
+                let stops = vec![
+                    GradientStop::new(0.0, self.sample(start)),
+                    GradientStop::new(1.0, self.sample(end)),
+                ];
+                Gradient::linear(start, end, stops).into()
+            }
+            PaintKind::RadialGradient => {
+                let stops = vec![
+                    GradientStop::new(0.0, self.sample(at)),
+                    GradientStop::new(1.0, self.sample(at + half)),
+                ];
+                Gradient::radial(at, reach / 2.0, stops).into()
+            }
+        };
+        Shape::new(geometry, paint)
+    }
+
+    // The reference color at `p`, half transparent.
+    fn sample(&self, p: Point<f64>) -> LinSrgba<f64> {
+        let sample = self
+            .reference
+            .get_pixel(p.x.max(0.0) as usize, p.y.max(0.0) as usize)
+            .unwrap_or(self.background);
+        LinSrgba::new(
+            sample.red as f64,
+            sample.green as f64,
+            sample.blue as f64,
+            0.5,
+        )
+    }
+}
+
+#[cfg(test)]
+mod engine_tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::geometry::{MultiPolygon, Orientation, PolygonWithHoles};
+
+    // Left half black, right half white.
+    fn split_reference(spec: CanvasSpec) -> Canvas {
+        let mut reference = Canvas::from_spec(spec);
+        for y in 0..spec.height {
+            for x in 0..spec.width / 2 {
+                reference
+                    .put_pixel(x, y, LinSrgba::new(0.0, 0.0, 0.0, 1.0))
+                    .unwrap();
+            }
+        }
+        reference
+    }
+
+    // Replaces the engine's drawing, as if it had been reached by accepted steps.
+    fn install(engine: &mut Engine, drawing: Drawing) {
+        engine.canvas = engine.render(&drawing).unwrap();
+        engine.prev_score = engine
+            .metric
+            .distance(&engine.canvas, &engine.reference)
+            .unwrap();
+        engine.drawing = drawing;
+    }
+
+    fn square(x: f64, y: f64, size: f64) -> Polygon<f64> {
+        Polygon::from_vec(vec![x, y, x + size, y, x + size, y + size, x, y + size]).unwrap()
+    }
+
+    #[test]
+    fn test_missing_reference_is_io_error() {
+        let result = Engine::new(
+            CanvasSpec::new(10, 10),
+            Path::new("this/file/does/not/exist.png"),
+        );
+        assert!(matches!(result, Err(Error::Io(_))));
+    }
+
+    #[test]
+    fn test_steps_never_increase_score() {
+        let spec = CanvasSpec::new(16, 16);
+        let mut engine =
+            Engine::from_reference(spec, &split_reference(spec), EngineOptions::default()).unwrap();
+        let mut score = engine.score();
+        for _ in 0..50 {
+            engine.step().unwrap();
+            assert!(engine.score() <= score);
+            score = engine.score();
+        }
+    }
+
+    #[test]
+    fn test_degenerate_shapes_rejected() {
+        let spec = CanvasSpec::new(16, 16);
+        let options = EngineOptions {
+            min_shape_area: f64::INFINITY,
+            ..EngineOptions::default()
+        };
+        let mut engine = Engine::from_reference(spec, &split_reference(spec), options).unwrap();
+        for _ in 0..20 {
+            assert!(!engine.step().unwrap());
+        }
+        assert!(engine.drawing().shapes.is_empty());
+    }
+
+    #[test]
+    fn test_only_mutated_shape_checked_for_area() {
+        let spec = CanvasSpec::new(16, 16);
+        let mut engine =
+            Engine::from_reference(spec, &split_reference(spec), EngineOptions::default()).unwrap();
+        // A shape below the minimum area must not block changes to the others
+        let mut drawing = engine.drawing().clone();
+        let black = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        drawing
+            .shapes
+            .push(Shape::new(square(12.0, 12.0, 0.5), black));
+        install(&mut engine, drawing);
+        let accepted = (0..50).filter(|_| engine.step().unwrap()).count();
+        assert!(accepted > 0);
+    }
+
+    #[test]
+    fn test_set_resolution_drops_small_shapes() {
+        let spec = CanvasSpec::new(32, 32);
+        let mut engine =
+            Engine::from_reference(spec, &split_reference(spec), EngineOptions::default()).unwrap();
+        let mut drawing = engine.drawing().clone();
+        let black = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        drawing
+            .shapes
+            .push(Shape::new(square(0.0, 0.0, 8.0), black));
+        drawing
+            .shapes
+            .push(Shape::new(square(20.0, 20.0, 2.0), black));
+        install(&mut engine, drawing);
+        // A quarter of the size leaves areas of 4 and 0.25 square pixels
+        engine.set_resolution(CanvasSpec::new(8, 8)).unwrap();
+        assert_eq!(engine.drawing().shapes.len(), 1);
+        assert_eq!(engine.drawing().shapes[0].geometry.area(), 4.0);
+    }
+
+    #[test]
+    fn test_set_resolution_scales_drawing() {
+        let spec = CanvasSpec::new(32, 16);
+        let mut engine =
+            Engine::from_reference(spec, &split_reference(spec), EngineOptions::default()).unwrap();
+        engine.set_resolution(CanvasSpec::new(8, 4)).unwrap();
+        while engine.drawing().shapes.is_empty() {
+            engine.step().unwrap();
+        }
+        let vertex = |engine: &Engine| {
+            engine.drawing().shapes[0]
+                .geometry
+                .as_polygon()
+                .unwrap()
+                .vertices[0]
+        };
+        let before = vertex(&engine);
+        engine.set_resolution(spec).unwrap();
+        let after = vertex(&engine);
+        assert_eq!(after, Point::new(before.x * 4.0, before.y * 4.0));
+        assert_eq!(engine.canvas().get_spec(), spec);
+        assert_eq!(engine.reference().get_spec(), spec);
+    }
+
+    #[test]
+    fn test_run_schedule() {
+        let spec = CanvasSpec::new(32, 32);
+        let mut engine =
+            Engine::from_reference(spec, &split_reference(spec), EngineOptions::default()).unwrap();
+        let initial = engine.score();
+        engine.run(&Schedule::coarse_to_fine(spec, 3, 100)).unwrap();
+        assert_eq!(engine.spec(), spec);
+        assert!(engine.score() < initial);
+    }
+
+    #[test]
+    fn test_simplify_pass_keeps_score() {
+        let spec = CanvasSpec::new(16, 16);
+        let mut engine =
+            Engine::from_reference(spec, &split_reference(spec), EngineOptions::default()).unwrap();
+        // A black square over the black half, with a redundant vertex on its top side
+        let square =
+            Polygon::from_vec(vec![0.0, 0.0, 4.0, 0.0, 8.0, 0.0, 8.0, 16.0, 0.0, 16.0]).unwrap();
+        let mut drawing = engine.drawing().clone();
+        drawing
+            .shapes
+            .push(Shape::new(square, LinSrgba::new(0.0, 0.0, 0.0, 1.0)));
+        install(&mut engine, drawing);
+        let score = engine.score();
+        assert_eq!(engine.simplify_pass(0.5).unwrap(), 1);
+        let simplified = engine.drawing().shapes[0].geometry.as_polygon().unwrap();
+        assert_eq!(simplified.vertices.len(), 4);
+        assert!(engine.score() <= score);
+    }
+
+    #[test]
+    fn test_convex_only() {
+        let spec = CanvasSpec::new(16, 16);
+        let options = EngineOptions {
+            convex_only: true,
+            ..EngineOptions::default()
+        };
+        let mut engine = Engine::from_reference(spec, &split_reference(spec), options).unwrap();
+        for _ in 0..200 {
+            engine.step().unwrap();
+        }
+        assert!(!engine.drawing().shapes.is_empty());
+        for shape in &engine.drawing().shapes {
+            assert!(shape.geometry.as_polygon().unwrap().is_convex());
+        }
+    }
+
+    #[test]
+    fn test_convex_only_skips_collapsed_hulls() {
+        let spec = CanvasSpec::new(16, 16);
+        let options = EngineOptions {
+            convex_only: true,
+            ..EngineOptions::default()
+        };
+        let mut engine = Engine::from_reference(spec, &split_reference(spec), options).unwrap();
+        // Two of the vertices coincide, so moving the third leaves only two distinct
+        // points
+        let pinched = Polygon::from_vec(vec![4.0, 8.0, 4.0, 8.0, 8.0, 12.0]).unwrap();
+        let mut skipped = 0;
+        for _ in 0..200 {
+            let mut geometry = Geometry::Polygon(pinched.clone());
+            engine.mutate_geometry(&mut geometry, 4.0);
+            let polygon = geometry.as_polygon().unwrap();
+            assert!(polygon.vertices.len() >= 3, "{:?}", polygon);
+            skipped += (polygon == &pinched) as usize;
+        }
+        assert!(skipped > 0);
+    }
+
+    #[test]
+    fn test_parallel_render_is_identical() {
+        let spec = CanvasSpec::new(24, 20);
+        let run = |parallel_render| {
+            let options = EngineOptions {
+                parallel_render,
+                ..EngineOptions::default()
+            };
+            let mut engine = Engine::from_reference(spec, &split_reference(spec), options).unwrap();
+            for _ in 0..100 {
+                engine.step().unwrap();
+            }
+            (engine.score(), engine.canvas().clone())
+        };
+        let (serial_score, serial) = run(false);
+        let (parallel_score, parallel) = run(true);
+        assert_eq!(serial_score, parallel_score);
+        assert_eq!(serial.pixels(), parallel.pixels());
+    }
+
+    #[test]
+    fn test_multi_polygon_mutations_keep_orientation() {
+        let spec = CanvasSpec::new(16, 16);
+        let mut engine =
+            Engine::from_reference(spec, &split_reference(spec), EngineOptions::default()).unwrap();
+        let frame = PolygonWithHoles::new(square(2.0, 2.0, 12.0), vec![square(6.0, 6.0, 4.0)]);
+        let mut geometry = Geometry::from(frame);
+        for _ in 0..200 {
+            // Moves far enough to turn small rings inside out
+            engine.mutate_geometry(&mut geometry, 16.0);
+            let Geometry::MultiPolygon(multi) = &geometry else {
+                panic!("{:?}", geometry);
+            };
+            let polygon = &multi.polygons[0];
+            assert_ne!(polygon.exterior.orientation(), Orientation::Clockwise);
+            assert_ne!(
+                polygon.holes[0].orientation(),
+                Orientation::CounterClockwise
+            );
+        }
+    }
+
+    #[test]
+    fn test_multi_polygon_mutations_skip_empty_rings() {
+        let spec = CanvasSpec::new(16, 16);
+        let mut engine =
+            Engine::from_reference(spec, &split_reference(spec), EngineOptions::default()).unwrap();
+        let frame = PolygonWithHoles::new(square(2.0, 2.0, 12.0), vec![Polygon::new()]);
+        let mut geometry = Geometry::from(frame);
+        for _ in 0..50 {
+            engine.mutate_geometry(&mut geometry, 4.0);
+        }
+        let Geometry::MultiPolygon(multi) = &geometry else {
+            panic!("{:?}", geometry);
+        };
+        assert!(multi.polygons[0].holes[0].vertices.is_empty());
+
+        let mut geometry = Geometry::from(MultiPolygon::new(vec![PolygonWithHoles::new(
+            Polygon::new(),
+            vec![],
+        )]));
+        engine.mutate_geometry(&mut geometry, 4.0);
+    }
+
+    #[test]
+    fn test_ellipse_shapes() {
+        let spec = CanvasSpec::new(16, 16);
+        let options = EngineOptions {
+            shape_kind: ShapeKind::Ellipse,
+            ..EngineOptions::default()
+        };
+        let mut engine = Engine::from_reference(spec, &split_reference(spec), options).unwrap();
+        let initial = engine.score();
+        for _ in 0..200 {
+            engine.step().unwrap();
+        }
+        assert!(engine.score() < initial);
+        assert!(!engine.drawing().shapes.is_empty());
+        for shape in &engine.drawing().shapes {
+            assert!(matches!(shape.geometry, Geometry::Ellipse(_)));
+        }
+    }
+
+    #[test]
+    fn test_blob_shapes() {
+        let spec = CanvasSpec::new(16, 16);
+        let options = EngineOptions {
+            shape_kind: ShapeKind::Blob,
+            ..EngineOptions::default()
+        };
+        let mut engine = Engine::from_reference(spec, &split_reference(spec), options).unwrap();
+        let initial = engine.score();
+        for _ in 0..200 {
+            engine.step().unwrap();
+        }
+        assert!(engine.score() < initial);
+        assert!(!engine.drawing().shapes.is_empty());
+        for shape in &engine.drawing().shapes {
+            assert!(matches!(shape.geometry, Geometry::Path(_)));
+        }
+    }
+
+    #[test]
+    fn test_gradient_paints() {
+        let spec = CanvasSpec::new(16, 16);
+        let options = EngineOptions {
+            paint_kind: PaintKind::LinearGradient,
+            ..EngineOptions::default()
+        };
+        let mut engine = Engine::from_reference(spec, &split_reference(spec), options).unwrap();
+        let initial = engine.score();
+        for _ in 0..200 {
+            engine.step().unwrap();
+        }
+        assert!(engine.score() < initial);
+        assert!(!engine.drawing().shapes.is_empty());
+        for shape in &engine.drawing().shapes {
+            assert!(matches!(
+                &shape.paint,
+                Paint::Gradient(Gradient {
+                    shape: GradientShape::Linear { .. },
+                    ..
+                })
+            ));
+        }
+    }
 }
@@ -1 +1,6 @@
+#[allow(clippy::module_inception)]
 pub mod engine;
+pub mod schedule;
+
+pub use engine::{Engine, EngineOptions, PaintKind, ShapeKind};
+pub use schedule::{Schedule, Stage};
@@ -0,0 +1,71 @@
+use crate::canvas::CanvasSpec;
+
+// Optimize at `spec` for `steps` mutation steps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stage {
+    pub spec: CanvasSpec,
+    pub steps: usize,
+}
+
+impl Stage {
+    pub fn new(spec: CanvasSpec, steps: usize) -> Self {
+        Stage { spec, steps }
+    }
+}
+
+// Resolution stages run in order. The drawing is rescaled between stages.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Schedule {
+    pub stages: Vec<Stage>,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Schedule { stages: Vec::new() }
+    }
+
+    pub fn stage(mut self, spec: CanvasSpec, steps: usize) -> Self {
+        self.stages.push(Stage::new(spec, steps));
+        self
+    }
+
+    /// `levels` stages ending at `spec`, each twice the size of the one before
+    /// (rounding up, never below 1x1), with `steps` steps per stage.
+    pub fn coarse_to_fine(spec: CanvasSpec, levels: usize, steps: usize) -> Self {
+        let mut stages = Vec::with_capacity(levels);
+        let mut current = spec;
+        for _ in 0..levels {
+            stages.push(Stage::new(current, steps));
+            current = CanvasSpec::new(
+                current.width.div_ceil(2).max(1),
+                current.height.div_ceil(2).max(1),
+            );
+        }
+        stages.reverse();
+        Schedule { stages }
+    }
+
+    pub fn total_steps(&self) -> usize {
+        self.stages.iter().map(|s| s.steps).sum()
+    }
+}
+
+#[cfg(test)]
+mod schedule_tests {
+    use super::*;
+
+    #[test]
+    fn test_coarse_to_fine() {
+        let schedule = Schedule::coarse_to_fine(CanvasSpec::new(100, 30), 3, 10);
+        let specs: Vec<_> = schedule.stages.iter().map(|s| s.spec).collect();
+        assert_eq!(
+            specs,
+            vec![
+                CanvasSpec::new(25, 8),
+                CanvasSpec::new(50, 15),
+                CanvasSpec::new(100, 30),
+            ]
+        );
+        assert_eq!(schedule.total_steps(), 30);
+    }
+}
@@ -0,0 +1,170 @@
+use std::fmt;
+
+use image::ImageError;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A flat coordinate list must hold (x, y) pairs.
+    OddCoordinateCount(usize),
+    /// A coordinate is NaN or infinite. `index` is the vertex index.
+    NonFiniteCoordinate {
+        index: usize,
+    },
+    /// A point of a path, possibly a control point, is NaN or infinite. `segment` is
+    /// the index of its segment.
+    NonFinitePathPoint {
+        segment: usize,
+    },
+    /// A parameter of a shape, such as a radius, is NaN or infinite.
+    NonFiniteParameter {
+        name: &'static str,
+    },
+    /// Shapes are given as dimension lists, e.g. [height, width, channels].
+    ShapeMismatch {
+        expected: Vec<usize>,
+        actual: Vec<usize>,
+    },
+    TooFewVertices {
+        required: usize,
+        actual: usize,
+    },
+    /// A pixel lies outside a `width` x `height` canvas.
+    OutOfBounds {
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    },
+    /// A `width` x `height` region with its top-left pixel at `(x, y)` reaches outside a
+    /// `canvas_width` x `canvas_height` canvas.
+    RegionOutOfBounds {
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        canvas_width: usize,
+        canvas_height: usize,
+    },
+    Io(std::io::Error),
+    Decode(ImageError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OddCoordinateCount(n) => write!(
+                f,
+                "expected an even number of coordinates (x, y pairs), got {}",
+                n
+            ),
+            Self::NonFiniteCoordinate { index } => {
+                write!(f, "vertex {} has a NaN or infinite coordinate", index)
+            }
+            Self::NonFinitePathPoint { segment } => {
+                write!(f, "path segment {} has a NaN or infinite point", segment)
+            }
+            Self::NonFiniteParameter { name } => write!(f, "{} is NaN or infinite", name),
+            Self::ShapeMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "shape mismatch: expected {:?}, got {:?}",
+                    expected, actual
+                )
+            }
+            Self::TooFewVertices { required, actual } => write!(
+                f,
+                "a polygon needs at least {} vertices, got {}",
+                required, actual
+            ),
+            Self::OutOfBounds {
+                x,
+                y,
+                width,
+                height,
+            } => write!(
+                f,
+                "({}, {}) is outside the {}x{} canvas",
+                x, y, width, height
+            ),
+            Self::RegionOutOfBounds {
+                x,
+                y,
+                width,
+                height,
+                canvas_width,
+                canvas_height,
+            } => write!(
+                f,
+                "the {}x{} region at ({}, {}) reaches outside the {}x{} canvas",
+                width, height, x, y, canvas_width, canvas_height
+            ),
+            Self::Io(err) => write!(f, "I/O error: {}", err),
+            Self::Decode(err) => write!(f, "failed to decode image: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Decode(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ImageError> for Error {
+    fn from(err: ImageError) -> Self {
+        match err {
+            // Keep plain I/O failures (missing file, permissions) apart from bad image data
+            ImageError::IoError(err) => Self::Io(err),
+            err => Self::Decode(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod error_tests {
+    use super::Error;
+
+    #[test]
+    fn test_display_odd_coordinate_count() {
+        let err = Error::OddCoordinateCount(5);
+        assert_eq!(
+            err.to_string(),
+            "expected an even number of coordinates (x, y pairs), got 5"
+        );
+    }
+
+    #[test]
+    fn test_display_region_out_of_bounds() {
+        let err = Error::RegionOutOfBounds {
+            x: 6,
+            y: 0,
+            width: 3,
+            height: 1,
+            canvas_width: 8,
+            canvas_height: 6,
+        };
+        assert_eq!(
+            err.to_string(),
+            "the 3x1 region at (6, 0) reaches outside the 8x6 canvas"
+        );
+    }
+
+    #[test]
+    fn test_image_io_error_is_io() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+        let err: Error = image::ImageError::IoError(io).into();
+        assert!(matches!(err, Error::Io(_)));
+    }
+}
@@ -0,0 +1,236 @@
+use num_traits::AsPrimitive;
+
+use super::{Line, Point, Polygon};
+
+// Maps (x, y) to (a * x + b * y + c, d * x + e * y + f).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Affine2 {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Default for Affine2 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Affine2 {
+    pub const IDENTITY: Affine2 = Affine2 {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 0.0,
+        e: 1.0,
+        f: 0.0,
+    };
+
+    pub fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        Affine2 { a, b, c, d, e, f }
+    }
+
+    pub fn translate(dx: f64, dy: f64) -> Self {
+        Self::new(1.0, 0.0, dx, 0.0, 1.0, dy)
+    }
+
+    /// Scales about the origin.
+    pub fn scale(sx: f64, sy: f64) -> Self {
+        Self::new(sx, 0.0, 0.0, 0.0, sy, 0.0)
+    }
+
+    pub fn scale_about(sx: f64, sy: f64, pivot: Point<f64>) -> Self {
+        Self::about(Self::scale(sx, sy), pivot)
+    }
+
+    /// Rotates by `angle` radians about the origin, from +x towards +y.
+    pub fn rotate(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(cos, -sin, 0.0, sin, cos, 0.0)
+    }
+
+    pub fn rotate_about(angle: f64, pivot: Point<f64>) -> Self {
+        Self::about(Self::rotate(angle), pivot)
+    }
+
+    /// Maps (x, y) to (x + kx * y, y + ky * x).
+    pub fn shear(kx: f64, ky: f64) -> Self {
+        Self::new(1.0, kx, 0.0, ky, 1.0, 0.0)
+    }
+
+    // Conjugates `t` so that it acts around `pivot` instead of the origin.
+    fn about(t: Self, pivot: Point<f64>) -> Self {
+        Self::translate(-pivot.x, -pivot.y)
+            .then(&t)
+            .then(&Self::translate(pivot.x, pivot.y))
+    }
+
+    /// The transform applying `self` first and `next` second.
+    pub fn then(&self, next: &Affine2) -> Self {
+        Affine2 {
+            a: next.a * self.a + next.b * self.d,
+            b: next.a * self.b + next.b * self.e,
+            c: next.a * self.c + next.b * self.f + next.c,
+            d: next.d * self.a + next.e * self.d,
+            e: next.d * self.b + next.e * self.e,
+            f: next.d * self.c + next.e * self.f + next.f,
+        }
+    }
+
+    pub fn determinant(&self) -> f64 {
+        self.a * self.e - self.b * self.d
+    }
+
+    /// The inverse transform, or `None` if the transform collapses the plane. The
+    /// determinant is compared to the size of the linear part, so uniformly tiny or huge
+    /// transforms still invert.
+    pub fn invert(&self) -> Option<Self> {
+        let det = self.determinant();
+        let size = self
+            .a
+            .abs()
+            .max(self.b.abs())
+            .max(self.d.abs())
+            .max(self.e.abs());
+        if !det.is_finite() || det.abs() <= f64::EPSILON * size * size {
+            return None;
+        }
+        let (a, b, d, e) = (self.e / det, -self.b / det, -self.d / det, self.a / det);
+        let inverse = Affine2 {
+            a,
+            b,
+            c: -(a * self.c + b * self.f),
+            d,
+            e,
+            f: -(d * self.c + e * self.f),
+        };
+        [
+            inverse.a, inverse.b, inverse.c, inverse.d, inverse.e, inverse.f,
+        ]
+        .iter()
+        .all(|v| v.is_finite())
+        .then_some(inverse)
+    }
+
+    pub fn apply(&self, p: Point<f64>) -> Point<f64> {
+        Point::new(
+            self.a * p.x + self.b * p.y + self.c,
+            self.d * p.x + self.e * p.y + self.f,
+        )
+    }
+}
+
+// Geometry that can be mapped through an `Affine2`.
+// Integer coordinates are truncated after transforming.
+pub trait Transform {
+    fn transform(&self, t: &Affine2) -> Self;
+}
+
+impl<T> Transform for Point<T>
+where
+    T: Copy + AsPrimitive<f64>,
+    f64: AsPrimitive<T>,
+{
+    fn transform(&self, t: &Affine2) -> Self {
+        let p = t.apply(Point::new(self.x.as_(), self.y.as_()));
+        Point::new(p.x.as_(), p.y.as_())
+    }
+}
+
+impl<T> Transform for Line<T>
+where
+    T: Copy + AsPrimitive<f64>,
+    f64: AsPrimitive<T>,
+{
+    fn transform(&self, t: &Affine2) -> Self {
+        Line::new(self.start.transform(t), self.end.transform(t))
+    }
+}
+
+impl<T> Transform for Polygon<T>
+where
+    T: Copy + AsPrimitive<f64>,
+    f64: AsPrimitive<T>,
+{
+    fn transform(&self, t: &Affine2) -> Self {
+        Polygon {
+            vertices: self.vertices.iter().map(|p| p.transform(t)).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod affine_tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn assert_point_eq(a: Point<f64>, b: Point<f64>) {
+        assert_relative_eq!(a.x, b.x, epsilon = 1e-12);
+        assert_relative_eq!(a.y, b.y, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_basic_transforms() {
+        let p = Point::new(2.0, 1.0);
+        assert_point_eq(Affine2::translate(1.0, -1.0).apply(p), Point::new(3.0, 0.0));
+        assert_point_eq(Affine2::scale(2.0, 3.0).apply(p), Point::new(4.0, 3.0));
+        assert_point_eq(Affine2::rotate(FRAC_PI_2).apply(p), Point::new(-1.0, 2.0));
+        assert_point_eq(Affine2::shear(1.0, 0.0).apply(p), Point::new(3.0, 1.0));
+    }
+
+    #[test]
+    fn test_pivots() {
+        let pivot = Point::new(1.0, 1.0);
+        assert_point_eq(
+            Affine2::rotate_about(FRAC_PI_2, pivot).apply(Point::new(2.0, 1.0)),
+            Point::new(1.0, 2.0),
+        );
+        assert_point_eq(
+            Affine2::scale_about(2.0, 2.0, pivot).apply(Point::new(2.0, 3.0)),
+            Point::new(3.0, 5.0),
+        );
+        assert_point_eq(Affine2::scale_about(5.0, 5.0, pivot).apply(pivot), pivot);
+    }
+
+    #[test]
+    fn test_then_order() {
+        let t = Affine2::translate(1.0, 0.0).then(&Affine2::scale(2.0, 2.0));
+        assert_point_eq(t.apply(Point::new(0.0, 0.0)), Point::new(2.0, 0.0));
+        let t = Affine2::scale(2.0, 2.0).then(&Affine2::translate(1.0, 0.0));
+        assert_point_eq(t.apply(Point::new(0.0, 0.0)), Point::new(1.0, 0.0));
+    }
+
+    #[test]
+    fn test_invert() {
+        let t = Affine2::rotate_about(0.7, Point::new(3.0, -2.0))
+            .then(&Affine2::shear(0.3, 0.1))
+            .then(&Affine2::scale(2.0, 0.5));
+        let inv = t.invert().unwrap();
+        let p = Point::new(5.0, 7.0);
+        assert_point_eq(inv.apply(t.apply(p)), p);
+        assert!(Affine2::scale(0.0, 1.0).invert().is_none());
+        // Small but well-conditioned
+        let tiny = Affine2::scale(1e-5, 1e-5);
+        assert_point_eq(tiny.invert().unwrap().apply(tiny.apply(p)), p);
+        // Rank one up to rounding
+        assert!(Affine2::shear(1.0, 1.0 - 1e-17).invert().is_none());
+        assert!(Affine2::scale(f64::NAN, 1.0).invert().is_none());
+    }
+
+    #[test]
+    fn test_transform_geometry() {
+        let poly = Polygon::from_vec(vec![0, 0, 2, 0, 2, 2]).unwrap();
+        let moved = poly.transform(&Affine2::translate(1.0, 3.0));
+        assert_eq!(moved, Polygon::from_vec(vec![1, 3, 3, 3, 3, 5]).unwrap());
+        let line = Line::new(Point::new(1.0, 0.0), Point::new(0.0, 1.0));
+        let scaled = line.transform(&Affine2::scale(2.0, 2.0));
+        assert_eq!(
+            scaled,
+            Line::new(Point::new(2.0, 0.0), Point::new(0.0, 2.0))
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use std::f64::consts::PI;
+
+use super::primitive::{check_params, Primitive};
+use super::{Affine2, Point, Polygon, Rect, Transform};
+use crate::error::Result;
+
+// Number of vertices approximating a full circle of `radius` within `tolerance`.
+pub(crate) fn circle_steps(radius: f64, tolerance: f64) -> usize {
+    let step = (1.0 - tolerance / radius).max(-1.0).acos();
+    ((PI / step).ceil() as usize).clamp(8, 1024)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+    pub center: Point<f64>,
+    pub radius: f64,
+}
+
+impl Circle {
+    pub fn new(center: Point<f64>, radius: f64) -> Self {
+        Circle { center, radius }
+    }
+}
+
+impl Primitive for Circle {
+    fn signed_distance(&self, p: Point<f64>) -> f64 {
+        let d = p - self.center;
+        d.x.hypot(d.y) - self.radius
+    }
+
+    fn bounding_box(&self) -> Rect<f64> {
+        let r = Point::new(self.radius, self.radius);
+        Rect::new(self.center - r, self.center + r)
+    }
+
+    fn area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+
+    fn flatten(&self, tolerance: f64) -> Polygon<f64> {
+        Ellipse::from(*self).flatten(tolerance)
+    }
+
+    fn check_finite(&self) -> Result<()> {
+        check_params(&[
+            ("center.x", self.center.x),
+            ("center.y", self.center.y),
+            ("radius", self.radius),
+        ])
+    }
+}
+
+/// An ellipse with semi-axes `rx` and `ry`, rotated by `angle` radians from +x towards +y.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ellipse {
+    pub center: Point<f64>,
+    pub rx: f64,
+    pub ry: f64,
+    pub angle: f64,
+}
+
+impl Ellipse {
+    pub fn new(center: Point<f64>, rx: f64, ry: f64, angle: f64) -> Self {
+        Ellipse {
+            center,
+            rx,
+            ry,
+            angle,
+        }
+    }
+
+    // `p` in the frame where the ellipse is centered and axis-aligned.
+    fn local(&self, p: Point<f64>) -> Point<f64> {
+        let (sin, cos) = self.angle.sin_cos();
+        let d = p - self.center;
+        Point::new(cos * d.x + sin * d.y, cos * d.y - sin * d.x)
+    }
+}
+
+impl From<Circle> for Ellipse {
+    fn from(circle: Circle) -> Self {
+        Ellipse::new(circle.center, circle.radius, circle.radius, 0.0)
+    }
+}
+
+impl Primitive for Ellipse {
+    // Exact on the outline and for circles. Elsewhere the implicit function is divided by
+    // its gradient, which is close enough within the pixel or so used for anti-aliasing.
+    fn signed_distance(&self, p: Point<f64>) -> f64 {
+        if self.rx <= 0.0 || self.ry <= 0.0 {
+            return f64::INFINITY;
+        }
+        let q = self.local(p);
+        if self.rx == self.ry {
+            return q.x.hypot(q.y) - self.rx;
+        }
+        let k0 = (q.x / self.rx).hypot(q.y / self.ry);
+        let k1 = (q.x / (self.rx * self.rx)).hypot(q.y / (self.ry * self.ry));
+        if k1 == 0.0 {
+            -self.rx.min(self.ry)
+        } else {
+            k0 * (k0 - 1.0) / k1
+        }
+    }
+
+    fn bounding_box(&self) -> Rect<f64> {
+        let (sin, cos) = self.angle.sin_cos();
+        let r = Point::new(
+            (self.rx * cos).hypot(self.ry * sin),
+            (self.rx * sin).hypot(self.ry * cos),
+        );
+        Rect::new(self.center - r, self.center + r)
+    }
+
+    fn area(&self) -> f64 {
+        PI * self.rx * self.ry
+    }
+
+    fn flatten(&self, tolerance: f64) -> Polygon<f64> {
+        let steps = circle_steps(self.rx.max(self.ry), tolerance);
+        let (sin, cos) = self.angle.sin_cos();
+        let vertices = (0..steps)
+            .map(|i| {
+                let t = 2.0 * PI * i as f64 / steps as f64;
+                let (x, y) = (self.rx * t.cos(), self.ry * t.sin());
+                self.center + Point::new(cos * x - sin * y, sin * x + cos * y)
+            })
+            .collect();
+        Polygon { vertices }
+    }
+
+    fn check_finite(&self) -> Result<()> {
+        check_params(&[
+            ("center.x", self.center.x),
+            ("center.y", self.center.y),
+            ("rx", self.rx),
+            ("ry", self.ry),
+            ("angle", self.angle),
+        ])
+    }
+}
+
+// The image of an ellipse is an ellipse. With M = R(angle) diag(rx, ry) and L the linear
+// part of `t`, the new axes are the eigenvectors of (LM)(LM)^T.
+impl Transform for Ellipse {
+    fn transform(&self, t: &Affine2) -> Self {
+        let (sin, cos) = self.angle.sin_cos();
+        let u = Point::new(t.a * cos + t.b * sin, t.d * cos + t.e * sin) * self.rx;
+        let v = Point::new(t.b * cos - t.a * sin, t.e * cos - t.d * sin) * self.ry;
+        let (a, b, c) = (
+            u.x * u.x + v.x * v.x,
+            u.x * u.y + v.x * v.y,
+            u.y * u.y + v.y * v.y,
+        );
+        let mean = (a + c) / 2.0;
+        let spread = ((a - c) / 2.0).hypot(b);
+        Ellipse {
+            center: t.apply(self.center),
+            rx: (mean + spread).sqrt(),
+            ry: (mean - spread).max(0.0).sqrt(),
+            angle: 0.5 * (2.0 * b).atan2(a - c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod ellipse_tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_circle() {
+        let circle = Circle::new(Point::new(5.0, 5.0), 2.0);
+        assert_relative_eq!(circle.signed_distance(Point::new(5.0, 8.0)), 1.0);
+        assert_relative_eq!(circle.signed_distance(Point::new(5.0, 5.0)), -2.0);
+        assert!(circle.contains(Point::new(6.0, 6.0)));
+        assert_eq!(
+            circle.bounding_box(),
+            Rect::new(Point::new(3.0, 3.0), Point::new(7.0, 7.0))
+        );
+        let flat = circle.flatten(0.01);
+        assert_relative_eq!(flat.signed_area(), circle.area(), max_relative = 0.01);
+    }
+
+    #[test]
+    fn test_rotated_ellipse() {
+        let ellipse = Ellipse::new(Point::new(0.0, 0.0), 4.0, 1.0, PI / 2.0);
+        assert!(ellipse.contains(Point::new(0.0, 3.5)));
+        assert!(!ellipse.contains(Point::new(3.5, 0.0)));
+        assert_relative_eq!(
+            ellipse.signed_distance(Point::new(0.0, 4.0)),
+            0.0,
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            ellipse.signed_distance(Point::new(2.0, 0.0)),
+            1.0,
+            epsilon = 1e-12
+        );
+        let bbox = ellipse.bounding_box();
+        assert_relative_eq!(bbox.max.x, 1.0, epsilon = 1e-12);
+        assert_relative_eq!(bbox.max.y, 4.0, epsilon = 1e-12);
+        assert!(ellipse
+            .flatten(0.1)
+            .vertices
+            .iter()
+            .all(|p| bbox.contains(*p)));
+    }
+
+    #[test]
+    fn test_transform_maps_outline() {
+        let ellipse = Ellipse::new(Point::new(3.0, -1.0), 5.0, 2.0, 0.4);
+        let t = Affine2::rotate(0.3)
+            .then(&Affine2::scale(2.0, 0.5))
+            .then(&Affine2::translate(1.0, 2.0));
+        let mapped = ellipse.transform(&t);
+        for p in &ellipse.flatten(0.1).vertices {
+            let d = mapped.signed_distance(t.apply(*p));
+            assert!(d.abs() < 1e-9, "{}", d);
+        }
+        assert_relative_eq!(
+            mapped.area(),
+            ellipse.area() * t.determinant().abs(),
+            max_relative = 1e-12
+        );
+    }
+}
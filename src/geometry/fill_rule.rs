@@ -0,0 +1,17 @@
+/// Which points a set of rings encloses: those with a nonzero winding number, or
+/// those crossed an odd number of times by a ray.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum FillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+}
+
+impl FillRule {
+    pub(crate) fn check(&self, n: i32) -> bool {
+        match self {
+            Self::NonZero => n != 0,
+            Self::EvenOdd => n % 2 != 0,
+        }
+    }
+}
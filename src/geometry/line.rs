@@ -27,6 +27,25 @@ where
     }
 }
 
+impl<T> Line<T>
+where
+    T: Copy + AsPrimitive<f64>,
+{
+    /// Euclidean distance from `p` to the closest point of the segment.
+    pub fn distance_to_point(&self, p: Point<f64>) -> f64 {
+        let (x0, y0): (f64, f64) = (self.start.x.as_(), self.start.y.as_());
+        let (x1, y1): (f64, f64) = (self.end.x.as_(), self.end.y.as_());
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let len_sq = dx * dx + dy * dy;
+        let t = if len_sq > 0.0 {
+            (((p.x - x0) * dx + (p.y - y0) * dy) / len_sq).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        (p.x - (x0 + t * dx)).hypot(p.y - (y0 + t * dy))
+    }
+}
+
 impl<T> Line<T>
 where
     T: Copy + PartialOrd,
@@ -92,4 +111,13 @@ mod line_tests {
         let line = Line::new(p1, p2);
         assert_eq!(line.y_max_point(), p2);
     }
+
+    #[test]
+    fn test_distance_to_point() {
+        let line = Line::new(Point::new(0.0, 0.0), Point::new(4.0, 0.0));
+        assert_eq!(line.distance_to_point(Point::new(2.0, 3.0)), 3.0);
+        assert_eq!(line.distance_to_point(Point::new(7.0, 4.0)), 5.0);
+        let dot = Line::new(Point::new(1, 1), Point::new(1, 1));
+        assert_eq!(dot.distance_to_point(Point::new(4.0, 5.0)), 5.0);
+    }
 }
@@ -1,7 +1,23 @@
+pub mod affine;
+pub mod ellipse;
+pub mod fill_rule;
 pub mod line;
+pub mod multi_polygon;
+pub mod path;
 pub mod point;
 pub mod polygon;
+pub mod primitive;
+pub mod rect;
+pub mod rounded_rect;
 
+pub use affine::{Affine2, Transform};
+pub use ellipse::{Circle, Ellipse};
+pub use fill_rule::FillRule;
 pub use line::Line;
+pub use multi_polygon::{MultiPolygon, PolygonWithHoles};
+pub use path::{Path, PathSegment};
 pub use point::Point;
-pub use polygon::Polygon;
+pub use polygon::{Orientation, Polygon};
+pub use primitive::{Geometry, Primitive};
+pub use rect::Rect;
+pub use rounded_rect::RoundedRect;
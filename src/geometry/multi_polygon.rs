@@ -0,0 +1,197 @@
+use super::{Affine2, FillRule, Orientation, Point, Polygon, Rect, Transform};
+use crate::error::Result;
+
+// A region bounded by one exterior ring, minus any holes. The exterior is
+// counter-clockwise and the holes clockwise (see `Orientation`).
+#[derive(Debug, PartialEq, Clone)]
+pub struct PolygonWithHoles {
+    pub exterior: Polygon<f64>,
+    pub holes: Vec<Polygon<f64>>,
+}
+
+impl PolygonWithHoles {
+    /// Reverses the rings running the wrong way, so the holes are cut out under either
+    /// `FillRule`.
+    pub fn new(exterior: Polygon<f64>, holes: Vec<Polygon<f64>>) -> Self {
+        let mut polygon = PolygonWithHoles { exterior, holes };
+        polygon.normalize();
+        polygon
+    }
+
+    /// Makes the exterior counter-clockwise and the holes clockwise, e.g. after moving
+    /// vertices through the public fields.
+    pub fn normalize(&mut self) {
+        if self.exterior.orientation() == Orientation::Clockwise {
+            self.exterior.reverse();
+        }
+        for hole in &mut self.holes {
+            if hole.orientation() == Orientation::CounterClockwise {
+                hole.reverse();
+            }
+        }
+    }
+
+    pub fn area(&self) -> f64 {
+        self.exterior.area() - self.holes.iter().map(|h| h.area()).sum::<f64>()
+    }
+
+    /// The exterior followed by the holes.
+    pub fn rings(&self) -> impl Iterator<Item = &Polygon<f64>> + '_ {
+        std::iter::once(&self.exterior).chain(&self.holes)
+    }
+
+    pub fn contains(&self, p: Point<f64>) -> bool {
+        self.exterior.contains(p, FillRule::NonZero)
+            && !self.holes.iter().any(|h| h.contains(p, FillRule::NonZero))
+    }
+}
+
+// A set of non-overlapping polygons with holes. Because the parts do not overlap and
+// holes wind opposite to their exterior, the rings fill the same region under either
+// `FillRule`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct MultiPolygon {
+    pub polygons: Vec<PolygonWithHoles>,
+}
+
+impl MultiPolygon {
+    pub fn new(polygons: Vec<PolygonWithHoles>) -> Self {
+        MultiPolygon { polygons }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.polygons.is_empty()
+    }
+
+    pub fn area(&self) -> f64 {
+        self.polygons.iter().map(|p| p.area()).sum()
+    }
+
+    /// Every ring of every part.
+    pub fn rings(&self) -> impl Iterator<Item = &Polygon<f64>> + '_ {
+        self.polygons.iter().flat_map(|p| p.rings())
+    }
+
+    pub fn rings_mut(&mut self) -> impl Iterator<Item = &mut Polygon<f64>> + '_ {
+        self.polygons
+            .iter_mut()
+            .flat_map(|p| std::iter::once(&mut p.exterior).chain(&mut p.holes))
+    }
+
+    pub fn contains(&self, p: Point<f64>) -> bool {
+        self.polygons.iter().any(|poly| poly.contains(p))
+    }
+
+    /// Normalizes the orientation of every part (see `PolygonWithHoles::normalize`).
+    pub fn normalize(&mut self) {
+        self.polygons
+            .iter_mut()
+            .for_each(PolygonWithHoles::normalize);
+    }
+
+    /// Whether `p` is inside the rings taken together under `rule`, as they are filled by
+    /// `spans_of_rings`. Agrees with `contains` when the parts are well formed.
+    pub fn contains_with(&self, p: Point<f64>, rule: FillRule) -> bool {
+        rings_contain(self.rings(), p, rule)
+    }
+
+    pub fn bounding_box(&self) -> Option<Rect<f64>> {
+        self.rings()
+            .filter_map(|ring| ring.bounding_box())
+            .reduce(Rect::union)
+    }
+
+    /// Fails on the first ring with a NaN or infinite coordinate.
+    pub fn check_finite(&self) -> Result<()> {
+        self.rings().try_for_each(|ring| ring.check_finite())
+    }
+}
+
+// Whether `p` is inside `rings` taken together under `rule`.
+pub(crate) fn rings_contain<'a>(
+    rings: impl Iterator<Item = &'a Polygon<f64>>,
+    p: Point<f64>,
+    rule: FillRule,
+) -> bool {
+    let n = match rule {
+        FillRule::NonZero => rings.map(|ring| ring.winding_number(p)).sum(),
+        FillRule::EvenOdd => rings.map(|ring| ring.crossing_count(p) as i32).sum(),
+    };
+    rule.check(n)
+}
+
+impl From<PolygonWithHoles> for MultiPolygon {
+    fn from(polygon: PolygonWithHoles) -> Self {
+        MultiPolygon::new(vec![polygon])
+    }
+}
+
+// Mirroring transforms reverse every ring, which `PolygonWithHoles::new` undoes.
+impl Transform for MultiPolygon {
+    fn transform(&self, t: &Affine2) -> Self {
+        MultiPolygon::new(
+            self.polygons
+                .iter()
+                .map(|p| {
+                    PolygonWithHoles::new(
+                        p.exterior.transform(t),
+                        p.holes.iter().map(|h| h.transform(t)).collect(),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod multi_polygon_tests {
+    use super::*;
+
+    #[test]
+    fn test_area_and_contains() {
+        let exterior = Polygon::from_vec(vec![0.0, 0.0, 4.0, 0.0, 4.0, 4.0, 0.0, 4.0]).unwrap();
+        let hole = Polygon::from_vec(vec![1.0, 1.0, 1.0, 3.0, 3.0, 3.0, 3.0, 1.0]).unwrap();
+        let multi = MultiPolygon::new(vec![PolygonWithHoles::new(exterior, vec![hole])]);
+        assert_eq!(multi.area(), 12.0);
+        assert_eq!(multi.rings().count(), 2);
+        assert!(multi.contains(Point::new(0.5, 0.5)));
+        assert!(!multi.contains(Point::new(2.0, 2.0)));
+        assert!(!multi.contains(Point::new(5.0, 2.0)));
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            assert!(multi.contains_with(Point::new(0.5, 0.5), rule));
+            assert!(!multi.contains_with(Point::new(2.0, 2.0), rule));
+        }
+        assert_eq!(
+            multi.bounding_box(),
+            Some(Rect::new(Point::new(0.0, 0.0), Point::new(4.0, 4.0)))
+        );
+
+        let mirrored = multi.transform(&Affine2::scale(-1.0, 1.0));
+        assert_eq!(mirrored.area(), 12.0);
+        assert!(mirrored.contains(Point::new(-0.5, 0.5)));
+        assert!(!mirrored.contains(Point::new(-2.0, 2.0)));
+    }
+
+    #[test]
+    fn test_rings_are_normalized() {
+        // Both rings given clockwise, as they would be drawn on screen
+        let mut exterior = Polygon::from_vec(vec![0.0, 0.0, 4.0, 0.0, 4.0, 4.0, 0.0, 4.0]).unwrap();
+        exterior.reverse();
+        let hole = Polygon::from_vec(vec![1.0, 1.0, 3.0, 1.0, 3.0, 3.0, 1.0, 3.0]).unwrap();
+        let mut polygon = PolygonWithHoles::new(exterior, vec![hole]);
+        assert_eq!(
+            polygon.exterior.orientation(),
+            Orientation::CounterClockwise
+        );
+        assert_eq!(polygon.holes[0].orientation(), Orientation::Clockwise);
+        assert_eq!(polygon.area(), 12.0);
+
+        polygon.holes[0].reverse();
+        let mut multi = MultiPolygon::from(polygon);
+        multi.normalize();
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            assert!(multi.contains_with(Point::new(0.5, 0.5), rule));
+            assert!(!multi.contains_with(Point::new(2.0, 2.0), rule));
+        }
+    }
+}
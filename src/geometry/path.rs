@@ -0,0 +1,449 @@
+use std::fmt::{self, Write};
+use std::sync::OnceLock;
+
+use super::{Affine2, Point, Polygon, Rect, Transform};
+use crate::error::{Error, Result};
+
+// Largest error of `Path::rings`, which a drawing fills, measures and hit-tests, so
+// that all three agree.
+const PATH_TOLERANCE: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathSegment {
+    /// Starts a new subpath.
+    MoveTo(Point<f64>),
+    LineTo(Point<f64>),
+    /// A quadratic Bézier curve through one control point.
+    QuadTo(Point<f64>, Point<f64>),
+    /// A cubic Bézier curve through two control points.
+    CubicTo(Point<f64>, Point<f64>, Point<f64>),
+    /// Ends the subpath with a line back to its start.
+    Close,
+}
+
+impl PathSegment {
+    fn map(&self, f: impl Fn(Point<f64>) -> Point<f64>) -> Self {
+        match *self {
+            PathSegment::MoveTo(p) => PathSegment::MoveTo(f(p)),
+            PathSegment::LineTo(p) => PathSegment::LineTo(f(p)),
+            PathSegment::QuadTo(c, p) => PathSegment::QuadTo(f(c), f(p)),
+            PathSegment::CubicTo(c0, c1, p) => PathSegment::CubicTo(f(c0), f(c1), f(p)),
+            PathSegment::Close => PathSegment::Close,
+        }
+    }
+
+    // Control points first, end point last.
+    fn points(&self) -> impl Iterator<Item = Point<f64>> {
+        let (points, len) = match *self {
+            PathSegment::MoveTo(p) | PathSegment::LineTo(p) => ([p, p, p], 1),
+            PathSegment::QuadTo(c, p) => ([c, p, p], 2),
+            PathSegment::CubicTo(c0, c1, p) => ([c0, c1, p], 3),
+            PathSegment::Close => ([Point::new(0.0, 0.0); 3], 0),
+        };
+        points.into_iter().take(len)
+    }
+
+    fn points_mut(&mut self) -> Vec<&mut Point<f64>> {
+        match self {
+            PathSegment::MoveTo(p) | PathSegment::LineTo(p) => vec![p],
+            PathSegment::QuadTo(c, p) => vec![c, p],
+            PathSegment::CubicTo(c0, c1, p) => vec![c0, c1, p],
+            PathSegment::Close => Vec::new(),
+        }
+    }
+}
+
+// Number of lines approximating a curve within `tolerance`, where `bound` limits the
+// curve's second derivative divided by 8. Evenly spaced chords then stay within
+// bound / n^2 of the curve.
+fn curve_steps(bound: f64, tolerance: f64) -> usize {
+    ((bound / tolerance).sqrt().ceil() as usize).clamp(1, 1024)
+}
+
+fn norm(p: Point<f64>) -> f64 {
+    p.x.hypot(p.y)
+}
+
+// Collects the vertices of one subpath at a time.
+struct Flattener {
+    rings: Vec<Polygon<f64>>,
+    vertices: Vec<Point<f64>>,
+    start: Point<f64>,
+    current: Point<f64>,
+}
+
+impl Flattener {
+    fn line_to(&mut self, p: Point<f64>) {
+        if self.vertices.is_empty() {
+            self.vertices.push(self.current);
+        }
+        self.vertices.push(p);
+        self.current = p;
+    }
+
+    // Subpaths with less than three distinct vertices enclose nothing and are dropped.
+    fn finish(&mut self) {
+        let mut vertices = std::mem::take(&mut self.vertices);
+        vertices.dedup();
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        if vertices.len() >= Polygon::<f64>::MIN_VERTICES {
+            self.rings.push(Polygon { vertices });
+        }
+    }
+}
+
+/// A sequence of subpaths made of lines and Bézier curves, as in SVG. Subpaths are
+/// filled as if closed.
+#[derive(Clone, Default)]
+pub struct Path {
+    segments: Vec<PathSegment>,
+    // `flatten(PATH_TOLERANCE)`, reset by every change to `segments`
+    rings: OnceLock<Vec<Polygon<f64>>>,
+}
+
+impl fmt::Debug for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Path")
+            .field("segments", &self.segments)
+            .finish()
+    }
+}
+
+impl PartialEq for Path {
+    fn eq(&self, other: &Self) -> bool {
+        self.segments == other.segments
+    }
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Path::default()
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    pub fn push(&mut self, segment: PathSegment) -> &mut Self {
+        self.rings.take();
+        self.segments.push(segment);
+        self
+    }
+
+    pub fn move_to(&mut self, p: Point<f64>) -> &mut Self {
+        self.push(PathSegment::MoveTo(p))
+    }
+
+    pub fn line_to(&mut self, p: Point<f64>) -> &mut Self {
+        self.push(PathSegment::LineTo(p))
+    }
+
+    pub fn quad_to(&mut self, control: Point<f64>, p: Point<f64>) -> &mut Self {
+        self.push(PathSegment::QuadTo(control, p))
+    }
+
+    pub fn cubic_to(&mut self, c0: Point<f64>, c1: Point<f64>, p: Point<f64>) -> &mut Self {
+        self.push(PathSegment::CubicTo(c0, c1, p))
+    }
+
+    pub fn close(&mut self) -> &mut Self {
+        self.push(PathSegment::Close)
+    }
+
+    /// One polygon per subpath, within `tolerance` of the curves. Each curve is split
+    /// into as few lines as its curvature allows, so flat curves cost a single line.
+    /// The rings are meant to be filled together, e.g. with `Rasterizer::fill_rings`.
+    pub fn flatten(&self, tolerance: f64) -> Vec<Polygon<f64>> {
+        let origin = Point::new(0.0, 0.0);
+        let mut flat = Flattener {
+            rings: Vec::new(),
+            vertices: Vec::new(),
+            start: origin,
+            current: origin,
+        };
+        for segment in &self.segments {
+            let p0 = flat.current;
+            match *segment {
+                PathSegment::MoveTo(p) => {
+                    flat.finish();
+                    flat.start = p;
+                    flat.current = p;
+                }
+                PathSegment::LineTo(p) => flat.line_to(p),
+                PathSegment::QuadTo(c, p) => {
+                    let n = curve_steps(norm(p0 - c * 2.0 + p) / 4.0, tolerance);
+                    for i in 1..=n {
+                        let t = i as f64 / n as f64;
+                        let s = 1.0 - t;
+                        flat.line_to(p0 * (s * s) + c * (2.0 * s * t) + p * (t * t));
+                    }
+                }
+                PathSegment::CubicTo(c0, c1, p) => {
+                    let dd = norm(p0 - c0 * 2.0 + c1).max(norm(c0 - c1 * 2.0 + p));
+                    let n = curve_steps(0.75 * dd, tolerance);
+                    for i in 1..=n {
+                        let t = i as f64 / n as f64;
+                        let s = 1.0 - t;
+                        flat.line_to(
+                            p0 * (s * s * s)
+                                + c0 * (3.0 * s * s * t)
+                                + c1 * (3.0 * s * t * t)
+                                + p * (t * t * t),
+                        );
+                    }
+                }
+                PathSegment::Close => {
+                    flat.finish();
+                    flat.current = flat.start;
+                }
+            }
+        }
+        flat.finish();
+        flat.rings
+    }
+
+    /// The rings a `Drawing` fills, measures and hit-tests, flattened within 0.1 on
+    /// first use and kept until the path changes.
+    pub fn rings(&self) -> &[Polygon<f64>] {
+        self.rings.get_or_init(|| self.flatten(PATH_TOLERANCE))
+    }
+
+    /// Every point of every segment in order, control points included.
+    pub fn points(&self) -> impl Iterator<Item = Point<f64>> + '_ {
+        self.segments.iter().flat_map(|s| s.points())
+    }
+
+    pub fn points_mut(&mut self) -> impl Iterator<Item = &mut Point<f64>> + '_ {
+        self.rings.take();
+        self.segments.iter_mut().flat_map(|s| s.points_mut())
+    }
+
+    /// The box around all points, control points included, which also contains the curves.
+    pub fn bounding_box(&self) -> Option<Rect<f64>> {
+        let points: Vec<Point<f64>> = self.points().collect();
+        Rect::from_points(&points)
+    }
+
+    /// Fails on the first segment with a NaN or infinite point.
+    pub fn check_finite(&self) -> Result<()> {
+        match self
+            .segments
+            .iter()
+            .position(|s| s.points().any(|p| !p.x.is_finite() || !p.y.is_finite()))
+        {
+            Some(segment) => Err(Error::NonFinitePathPoint { segment }),
+            None => Ok(()),
+        }
+    }
+
+    /// The path as the `d` attribute of an SVG `<path>`, keeping the curves. Fails like
+    /// `check_finite`, as SVG has no NaN or infinite numbers.
+    pub fn to_svg_data(&self) -> Result<String> {
+        self.check_finite()?;
+        let mut data = String::new();
+        for segment in &self.segments {
+            if !data.is_empty() {
+                data.push(' ');
+            }
+            let command = match segment {
+                PathSegment::MoveTo(_) => 'M',
+                PathSegment::LineTo(_) => 'L',
+                PathSegment::QuadTo(..) => 'Q',
+                PathSegment::CubicTo(..) => 'C',
+                PathSegment::Close => 'Z',
+            };
+            data.push(command);
+            for (i, p) in segment.points().enumerate() {
+                let separator = if i == 0 { "" } else { " " };
+                // Writing to a String cannot fail
+                let _ = write!(data, "{}{} {}", separator, p.x, p.y);
+            }
+        }
+        Ok(data)
+    }
+}
+
+impl From<&Polygon<f64>> for Path {
+    fn from(polygon: &Polygon<f64>) -> Self {
+        let mut path = Path::new();
+        let mut vertices = polygon.vertices.iter();
+        if let Some(&first) = vertices.next() {
+            path.move_to(first);
+            for &p in vertices {
+                path.line_to(p);
+            }
+            path.close();
+        }
+        path
+    }
+}
+
+// Bézier curves are affine invariant, so mapping the points maps the curves exactly.
+impl Transform for Path {
+    fn transform(&self, t: &Affine2) -> Self {
+        self.segments
+            .iter()
+            .map(|s| s.map(|p| t.apply(p)))
+            .collect()
+    }
+}
+
+impl Extend<PathSegment> for Path {
+    fn extend<I: IntoIterator<Item = PathSegment>>(&mut self, segments: I) {
+        self.rings.take();
+        self.segments.extend(segments);
+    }
+}
+
+impl FromIterator<PathSegment> for Path {
+    fn from_iter<I: IntoIterator<Item = PathSegment>>(segments: I) -> Self {
+        Path {
+            segments: segments.into_iter().collect(),
+            rings: OnceLock::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod path_tests {
+    use super::*;
+    use crate::algorithms::rasterizer::Rasterizer;
+    use crate::canvas::{BlendMode, Canvas, CanvasSpec};
+    use crate::geometry::FillRule;
+    use approx::assert_relative_eq;
+    use palette::rgb::LinSrgba;
+    use std::f64::consts::PI;
+
+    // A circle of four cubic arcs.
+    fn circle(center: Point<f64>, r: f64) -> Path {
+        let k = 0.552_284_749_8 * r;
+        let at = |x: f64, y: f64| center + Point::new(x, y);
+        let mut path = Path::new();
+        path.move_to(at(r, 0.0))
+            .cubic_to(at(r, k), at(k, r), at(0.0, r))
+            .cubic_to(at(-k, r), at(-r, k), at(-r, 0.0))
+            .cubic_to(at(-r, -k), at(-k, -r), at(0.0, -r))
+            .cubic_to(at(k, -r), at(r, -k), at(r, 0.0))
+            .close();
+        path
+    }
+
+    #[test]
+    fn test_flatten_is_adaptive() {
+        let path = circle(Point::new(0.0, 0.0), 10.0);
+        let coarse = path.flatten(1.0);
+        let fine = path.flatten(0.001);
+        assert_eq!((coarse.len(), fine.len()), (1, 1));
+        assert!(coarse[0].vertices.len() < fine[0].vertices.len());
+        for (rings, tolerance) in [(&coarse, 1.0), (&fine, 0.001)] {
+            assert!(rings[0].signed_area() > 0.0);
+            for edge in rings[0].edges() {
+                let mid = (edge.start + edge.end) * 0.5;
+                // The circle of cubic arcs itself is off by about 0.0003 of the radius
+                assert!((mid.x.hypot(mid.y) - 10.0).abs() < tolerance + 0.003);
+            }
+        }
+        assert_relative_eq!(fine[0].signed_area(), 100.0 * PI, max_relative = 1e-3);
+
+        // A straight curve needs a single line
+        let mut flat = Path::new();
+        flat.move_to(Point::new(0.0, 0.0))
+            .quad_to(Point::new(1.0, 0.0), Point::new(2.0, 0.0))
+            .line_to(Point::new(2.0, 2.0));
+        assert_eq!(flat.flatten(0.01)[0].vertices.len(), 3);
+    }
+
+    #[test]
+    fn test_subpaths() {
+        let mut path = Path::from(&Polygon::from_vec(vec![0.0, 0.0, 4.0, 0.0, 4.0, 4.0]).unwrap());
+        // A degenerate subpath is dropped, an unclosed one is closed implicitly
+        path.move_to(Point::new(9.0, 9.0))
+            .line_to(Point::new(10.0, 9.0))
+            .move_to(Point::new(5.0, 0.0))
+            .line_to(Point::new(6.0, 0.0))
+            .line_to(Point::new(6.0, 1.0));
+        let rings = path.flatten(0.1);
+        assert_eq!(rings.len(), 2);
+        assert_eq!(rings[0].vertices.len(), 3);
+        assert_relative_eq!(rings[1].area(), 0.5);
+        assert_eq!(
+            path.bounding_box(),
+            Some(Rect::new(Point::new(0.0, 0.0), Point::new(10.0, 9.0)))
+        );
+    }
+
+    #[test]
+    fn test_rings_follow_changes() {
+        let mut path = Path::from(&Polygon::from_vec(vec![0.0, 0.0, 4.0, 0.0, 4.0, 4.0]).unwrap());
+        assert_eq!(path.rings(), path.flatten(PATH_TOLERANCE));
+        // The cached rings are shared, not flattened again
+        assert!(std::ptr::eq(path.rings(), path.rings()));
+        let copy = path.clone();
+        for p in path.points_mut() {
+            *p = *p * 2.0;
+        }
+        assert_relative_eq!(path.rings()[0].area(), 32.0);
+        assert_relative_eq!(copy.rings()[0].area(), 8.0);
+        path.move_to(Point::new(10.0, 0.0))
+            .line_to(Point::new(11.0, 0.0))
+            .line_to(Point::new(11.0, 1.0));
+        assert_eq!(path.rings().len(), 2);
+        path.extend(copy.segments().iter().copied());
+        assert_eq!(path.rings(), path.flatten(PATH_TOLERANCE));
+        assert_eq!(path.rings().len(), 3);
+    }
+
+    #[test]
+    fn test_svg_data() {
+        let mut path = Path::new();
+        path.move_to(Point::new(0.0, 0.5))
+            .quad_to(Point::new(1.0, 2.0), Point::new(3.0, 0.0))
+            .cubic_to(
+                Point::new(1.0, 1.0),
+                Point::new(-1.5, 2.0),
+                Point::new(0.0, 0.5),
+            )
+            .close();
+        assert_eq!(
+            path.to_svg_data().unwrap(),
+            "M0 0.5 Q1 2 3 0 C1 1 -1.5 2 0 0.5 Z"
+        );
+        assert_eq!(Path::new().to_svg_data().unwrap(), "");
+
+        // The bad point is a control point of the second segment
+        *path.points_mut().nth(1).unwrap() = Point::new(f64::NAN, 2.0);
+        assert!(matches!(
+            path.to_svg_data(),
+            Err(Error::NonFinitePathPoint { segment: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_transform_and_fill() {
+        let path = circle(Point::new(0.0, 0.0), 1.0)
+            .transform(&Affine2::scale(8.0, 8.0).then(&Affine2::translate(10.0, 10.0)));
+        assert!(path.check_finite().is_ok());
+        let mut canvas = Canvas::white(CanvasSpec::new(20, 20));
+        let black = LinSrgba::new(0.0, 0.0, 0.0, 1.0);
+        Rasterizer::new()
+            .fill_rings(
+                &mut canvas,
+                &path.flatten(0.1),
+                black,
+                FillRule::NonZero,
+                BlendMode::Normal,
+            )
+            .unwrap();
+        let filled = canvas.pixels().iter().filter(|p| p.red == 0.0).count();
+        assert_relative_eq!(filled as f64, 64.0 * PI, max_relative = 0.05);
+
+        let mut bad = path.clone();
+        bad.line_to(Point::new(f64::INFINITY, 0.0));
+        assert!(matches!(
+            bad.check_finite(),
+            Err(Error::NonFinitePathPoint { segment: 6 })
+        ));
+    }
+}
@@ -1,3 +1,5 @@
+use std::ops::{Add, Mul, Sub};
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Point<T> {
     pub x: T,
@@ -10,6 +12,40 @@ impl<T: Copy> Point<T> {
     }
 }
 
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Point<T>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Point {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Point<T>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Point {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
+
+// Scaling by a scalar
+impl<T: Copy + Mul<Output = T>> Mul<T> for Point<T> {
+    type Output = Point<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Point {
+            x: self.x * rhs,
+            y: self.y * rhs,
+        }
+    }
+}
+
 #[cfg(test)]
 mod point_tests {
     use super::Point;
@@ -20,4 +56,13 @@ mod point_tests {
         assert_eq!(p.x, 1.0);
         assert_eq!(p.y, 2.0);
     }
+
+    #[test]
+    fn test_point_arithmetic() {
+        let p = Point::new(1, 2);
+        let q = Point::new(4, -1);
+        assert_eq!(p + q, Point::new(5, 1));
+        assert_eq!(q - p, Point::new(3, -3));
+        assert_eq!(p * 3, Point::new(3, 6));
+    }
 }
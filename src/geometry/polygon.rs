@@ -1,31 +1,257 @@
+use num_traits::{AsPrimitive, Num};
+
+use super::FillRule;
 use super::Line;
+use super::MultiPolygon;
 use super::Point;
+use super::Rect;
+use crate::algorithms::boolean::{boolean_op, BooleanOp};
+use crate::algorithms::convex_hull::convex_hull;
+use crate::algorithms::intersections::self_intersections;
+use crate::algorithms::simplify;
+use crate::error::{Error, Result};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Polygon<T: Copy> {
     pub vertices: Vec<Point<T>>,
 }
 
-impl<T> Polygon<T> where
-    T: Copy
-        + std::ops::Add<Output = T>
-        + std::ops::Sub<Output = T>
-        + std::ops::Mul<Output = T>
-        + PartialOrd
-        + std::convert::From<f64>
+// Orientation by the sign of the shoelace area: positive is counter-clockwise with
+// the y axis pointing up, which appears clockwise on screen where y points down.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Orientation {
+    Clockwise,
+    CounterClockwise,
+    Degenerate,
+}
+
+impl<T> Polygon<T>
+where
+    T: Copy + Num + PartialOrd + AsPrimitive<f64>,
+{
+    /// Shoelace area, positive for counter-clockwise vertices (see `Orientation`).
+    pub fn signed_area(&self) -> f64 {
+        self.edges()
+            .map(|e| {
+                let (x0, y0): (f64, f64) = (e.start.x.as_(), e.start.y.as_());
+                let (x1, y1): (f64, f64) = (e.end.x.as_(), e.end.y.as_());
+                x0 * y1 - x1 * y0
+            })
+            .sum::<f64>()
+            / 2.0
+    }
+
+    pub fn area(&self) -> f64 {
+        self.signed_area().abs()
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        self.edges()
+            .map(|e| {
+                let (x0, y0): (f64, f64) = (e.start.x.as_(), e.start.y.as_());
+                let (x1, y1): (f64, f64) = (e.end.x.as_(), e.end.y.as_());
+                (x1 - x0).hypot(y1 - y0)
+            })
+            .sum()
+    }
+
+    /// Area centroid. Falls back to the vertex mean when the area is zero,
+    /// and is `None` for a polygon without vertices.
+    pub fn centroid(&self) -> Option<Point<f64>> {
+        if self.vertices.is_empty() {
+            return None;
+        }
+        let area = self.signed_area();
+        if area.abs() < f64::EPSILON {
+            let n = self.vertices.len() as f64;
+            let (sx, sy) = self
+                .vertices
+                .iter()
+                .fold((0.0, 0.0), |(sx, sy), p| (sx + p.x.as_(), sy + p.y.as_()));
+            return Some(Point::new(sx / n, sy / n));
+        }
+        let (cx, cy) = self.edges().fold((0.0, 0.0), |(cx, cy), e| {
+            let (x0, y0): (f64, f64) = (e.start.x.as_(), e.start.y.as_());
+            let (x1, y1): (f64, f64) = (e.end.x.as_(), e.end.y.as_());
+            let cross = x0 * y1 - x1 * y0;
+            (cx + (x0 + x1) * cross, cy + (y0 + y1) * cross)
+        });
+        Some(Point::new(cx / (6.0 * area), cy / (6.0 * area)))
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        let area = self.signed_area();
+        if area > 0.0 {
+            Orientation::CounterClockwise
+        } else if area < 0.0 {
+            Orientation::Clockwise
+        } else {
+            Orientation::Degenerate
+        }
+    }
+
+    /// Whether the polygon is simple and convex. Collinear vertices are allowed,
+    /// but zero-area polygons and self-intersecting ones (e.g. pentagrams) are not.
+    pub fn is_convex(&self) -> bool {
+        let n = self.vertices.len();
+        if n < Self::MIN_VERTICES || self.orientation() == Orientation::Degenerate {
+            return false;
+        }
+        let mut sign = 0f64;
+        // A convex polygon changes direction at most twice along each axis
+        let mut x_flips = 0;
+        let mut y_flips = 0;
+        let mut prev_dx = 0f64;
+        let mut prev_dy = 0f64;
+        for i in 0..n {
+            let [a, b, c] = [i, i + 1, i + 2].map(|k| {
+                let p = self.vertices[k % n];
+                Point::<f64>::new(p.x.as_(), p.y.as_())
+            });
+            let (dx, dy) = (b.x - a.x, b.y - a.y);
+            let (ex, ey) = (c.x - b.x, c.y - b.y);
+            let cross = dx * ey - dy * ex;
+            if cross != 0.0 {
+                if sign != 0.0 && cross.signum() != sign {
+                    return false;
+                }
+                sign = cross.signum();
+            }
+            if dx != 0.0 {
+                if prev_dx != 0.0 && dx.signum() != prev_dx.signum() {
+                    x_flips += 1;
+                }
+                prev_dx = dx;
+            }
+            if dy != 0.0 {
+                if prev_dy != 0.0 && dy.signum() != prev_dy.signum() {
+                    y_flips += 1;
+                }
+                prev_dy = dy;
+            }
+        }
+        // Flips are counted without wrapping around, which misses at most one. Flips
+        // around a closed loop come in pairs, so this still allows only two per axis
+        x_flips <= 2 && y_flips <= 2
+    }
+
+    // Calls `f(direction)` for every edge crossing the horizontal ray from `p` towards
+    // -x, including crossings exactly at `p`. Edges are half-open in y and intersected
+    // the same way as in the scanline fill, so both agree at its sample positions.
+    fn for_each_crossing(&self, p: Point<f64>, mut f: impl FnMut(i32)) {
+        for edge in self.edges() {
+            let Some(inv_slope) = edge.inv_slope() else {
+                continue;
+            };
+            let low = edge.y_min_point();
+            let (y_min, y_max): (f64, f64) = (low.y.as_(), edge.y_max_point().y.as_());
+            if p.y < y_min || p.y >= y_max {
+                continue;
+            }
+            let x = low.x.as_() + (p.y - y_min) * inv_slope;
+            if x <= p.x {
+                f(if edge.start.y < edge.end.y { 1 } else { -1 });
+            }
+        }
+    }
+
+    /// Signed number of times the boundary winds around `p`.
+    pub fn winding_number(&self, p: Point<f64>) -> i32 {
+        let mut winding = 0;
+        self.for_each_crossing(p, |direction| winding += direction);
+        winding
+    }
+
+    /// Number of boundary crossings on a horizontal ray from `p`.
+    pub fn crossing_count(&self, p: Point<f64>) -> u32 {
+        let mut count = 0;
+        self.for_each_crossing(p, |_| count += 1);
+        count
+    }
+
+    pub fn contains(&self, p: Point<f64>, rule: FillRule) -> bool {
+        match rule {
+            FillRule::NonZero => self.winding_number(p) != 0,
+            FillRule::EvenOdd => self.crossing_count(p) % 2 == 1,
+        }
+    }
+
+    /// Distance from `p` to the boundary, whether `p` is inside or not.
+    /// Infinite for a polygon without vertices.
+    pub fn distance_to(&self, p: Point<f64>) -> f64 {
+        self.edges()
+            .map(|e| e.distance_to_point(p))
+            .fold(f64::INFINITY, f64::min)
+    }
+}
+
+impl<T> Polygon<T>
+where
+    T: Copy + PartialOrd,
 {
+    pub fn bounding_box(&self) -> Option<Rect<T>> {
+        Rect::from_points(&self.vertices)
+    }
+}
+
+impl<T> Polygon<T>
+where
+    T: Copy + PartialOrd + AsPrimitive<f64>,
+{
+    /// The smallest convex polygon around all vertices, counter-clockwise.
+    pub fn convex_hull(&self) -> Self {
+        convex_hull(&self.vertices)
+    }
+}
+
+impl<T: Copy> Default for Polygon<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<T: Copy> Polygon<T> {
+    pub const MIN_VERTICES: usize = 3;
+
     pub fn new() -> Self {
         Polygon {
             vertices: Vec::new(),
         }
     }
 
-    pub fn from_vec(v: Vec<T>) -> Option<Self> {
-        if v.len() % 2 != 0 {
-            return None;
+    pub fn add_point(&mut self, point: Point<T>) -> &mut Self {
+        self.vertices.push(point);
+        self
+    }
+
+    /// Reverses the vertex order, flipping the orientation.
+    pub fn reverse(&mut self) -> &mut Self {
+        self.vertices.reverse();
+        self
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = Line<T>> + '_ {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+            .map(|(&x, &y)| Line { start: x, end: y })
+    }
+}
+
+impl<T> Polygon<T>
+where
+    T: Copy + AsPrimitive<f64>,
+{
+    /// Builds a polygon from a flat `[x0, y0, x1, y1, ...]` coordinate list.
+    pub fn from_vec(v: Vec<T>) -> Result<Self> {
+        if !v.len().is_multiple_of(2) {
+            return Err(Error::OddCoordinateCount(v.len()));
+        }
+        if v.len() / 2 < Self::MIN_VERTICES {
+            return Err(Error::TooFewVertices {
+                required: Self::MIN_VERTICES,
+                actual: v.len() / 2,
+            });
         }
 
         let mut poly = Self::new();
@@ -35,27 +261,68 @@ impl<T: Copy> Polygon<T> {
                 y: v[i + 1],
             });
         }
-        Some(poly)
+        poly.check_finite()?;
+        Ok(poly)
     }
 
-    pub fn add_point(&mut self, point: Point<T>) -> &mut Self {
-        self.vertices.push(point);
-        self
+    /// Whether the boundary never touches or crosses itself.
+    pub fn is_simple(&self) -> bool {
+        self.vertices.len() >= Self::MIN_VERTICES && self_intersections(self).is_empty()
     }
 
-    pub fn edges(&self) -> impl Iterator<Item = Line<T>> + '_ {
-        return self
+    /// Splits the polygon into simple, non-overlapping rings covering the same region
+    /// as it does under `rule`.
+    pub fn repair(&self, rule: FillRule) -> MultiPolygon {
+        let poly = Polygon {
+            vertices: self
+                .vertices
+                .iter()
+                .map(|p| Point::new(p.x.as_(), p.y.as_()))
+                .collect(),
+        };
+        boolean_op(&[poly], &[], BooleanOp::Union, rule)
+    }
+
+    /// See `algorithms::simplify::rdp`.
+    pub fn simplify_rdp(&self, tolerance: f64) -> Self {
+        simplify::rdp(self, tolerance)
+    }
+
+    /// See `algorithms::simplify::visvalingam`.
+    pub fn simplify_visvalingam(&self, min_area: f64) -> Self {
+        simplify::visvalingam(self, min_area)
+    }
+
+    /// Drops consecutive vertices closer than `tolerance`.
+    pub fn dedup_vertices(&self, tolerance: f64) -> Self {
+        simplify::dedup(self, tolerance)
+    }
+
+    /// Drops vertices within `tolerance` of the line through their neighbors.
+    pub fn remove_collinear(&self, tolerance: f64) -> Self {
+        simplify::remove_collinear(self, tolerance)
+    }
+
+    /// Fails on the first vertex with a NaN or infinite coordinate.
+    pub fn check_finite(&self) -> Result<()> {
+        match self
             .vertices
             .iter()
-            .zip(self.vertices.iter().cycle().skip(1))
-            .map(|(&x, &y)| Line { start: x, end: y });
+            .position(|p| !p.x.as_().is_finite() || !p.y.as_().is_finite())
+        {
+            Some(index) => Err(Error::NonFiniteCoordinate { index }),
+            None => Ok(()),
+        }
     }
 }
 
 #[cfg(test)]
 mod polygon_tests {
-    use super::Polygon;
-    use crate::geometry::Point;
+    use super::{Orientation, Polygon};
+    use crate::error::Error;
+    use crate::geometry::FillRule;
+    use crate::geometry::{Point, Rect};
+    use approx::assert_relative_eq;
 
     #[test]
     fn test_polygon_creation() {
@@ -71,4 +338,160 @@ mod polygon_tests {
         assert_eq!(polygon.vertices.len(), 1);
         assert_eq!(polygon.vertices[0], p);
     }
+
+    #[test]
+    fn test_from_vec_odd_length() {
+        let result = Polygon::from_vec(vec![0, 0, 1, 0, 1]);
+        assert!(matches!(result, Err(Error::OddCoordinateCount(5))));
+    }
+
+    #[test]
+    fn test_from_vec_too_few_vertices() {
+        let result = Polygon::from_vec(vec![0, 0, 1, 0]);
+        assert!(matches!(
+            result,
+            Err(Error::TooFewVertices {
+                required: 3,
+                actual: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_from_vec_non_finite() {
+        let result = Polygon::from_vec(vec![0.0, 0.0, 1.0, f64::NAN, 1.0, 1.0]);
+        assert!(matches!(
+            result,
+            Err(Error::NonFiniteCoordinate { index: 1 })
+        ));
+        let result = Polygon::from_vec(vec![0.0, 0.0, 1.0, 0.0, f32::INFINITY, 1.0]);
+        assert!(matches!(
+            result,
+            Err(Error::NonFiniteCoordinate { index: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_area_and_perimeter() {
+        let square = Polygon::from_vec(vec![0, 0, 4, 0, 4, 3, 0, 3]).unwrap();
+        assert_relative_eq!(square.signed_area(), 12.0);
+        assert_relative_eq!(square.perimeter(), 14.0);
+        let triangle = Polygon::from_vec(vec![0.0, 0.0, 0.0, 4.0, 3.0, 0.0]).unwrap();
+        assert_relative_eq!(triangle.signed_area(), -6.0);
+        assert_relative_eq!(triangle.area(), 6.0);
+        assert_relative_eq!(triangle.perimeter(), 12.0);
+    }
+
+    #[test]
+    fn test_centroid() {
+        let square = Polygon::from_vec(vec![0.0, 0.0, 4.0, 0.0, 4.0, 2.0, 0.0, 2.0]).unwrap();
+        assert_eq!(square.centroid(), Some(Point::new(2.0, 1.0)));
+        let flat = Polygon::from_vec(vec![0.0, 0.0, 2.0, 0.0, 4.0, 0.0]).unwrap();
+        assert_eq!(flat.centroid(), Some(Point::new(2.0, 0.0)));
+        assert_eq!(Polygon::<f64>::new().centroid(), None);
+    }
+
+    #[test]
+    fn test_orientation_and_reverse() {
+        let mut poly = Polygon::from_vec(vec![0, 0, 4, 0, 4, 3]).unwrap();
+        assert_eq!(poly.orientation(), Orientation::CounterClockwise);
+        poly.reverse();
+        assert_eq!(poly.orientation(), Orientation::Clockwise);
+        let flat = Polygon::from_vec(vec![0, 0, 1, 1, 2, 2]).unwrap();
+        assert_eq!(flat.orientation(), Orientation::Degenerate);
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let poly = Polygon::from_vec(vec![1.0, 5.0, -2.0, 3.0, 4.0, -1.0]).unwrap();
+        assert_eq!(
+            poly.bounding_box(),
+            Some(Rect::new(Point::new(-2.0, -1.0), Point::new(4.0, 5.0)))
+        );
+        assert_eq!(Polygon::<f64>::new().bounding_box(), None);
+    }
+
+    #[test]
+    fn test_is_convex() {
+        let square = Polygon::from_vec(vec![0, 0, 4, 0, 4, 4, 0, 4]).unwrap();
+        assert!(square.is_convex());
+        let collinear = Polygon::from_vec(vec![0, 0, 2, 0, 4, 0, 4, 4, 0, 4]).unwrap();
+        assert!(collinear.is_convex());
+        let dart = Polygon::from_vec(vec![0, 0, 4, 2, 8, 0, 4, 8]).unwrap();
+        assert!(!dart.is_convex());
+        let pentagram = Polygon::from_vec(vec![
+            0.0, -10.0, 5.88, 8.09, -9.51, -3.09, 9.51, -3.09, -5.88, 8.09,
+        ])
+        .unwrap();
+        assert!(!pentagram.is_convex());
+        let flat = Polygon::from_vec(vec![0, 0, 1, 1, 2, 2]).unwrap();
+        assert!(!flat.is_convex());
+    }
+
+    #[test]
+    fn test_unsigned_coordinates() {
+        // Edges running towards smaller coordinates must not underflow
+        let square: Polygon<u32> = Polygon::from_vec(vec![0, 0, 4, 0, 4, 3, 0, 3]).unwrap();
+        assert_relative_eq!(square.signed_area(), 12.0);
+        assert_relative_eq!(square.perimeter(), 14.0);
+        assert!(square.is_convex());
+        let dart: Polygon<u32> = Polygon::from_vec(vec![0, 0, 4, 2, 8, 0, 4, 8]).unwrap();
+        assert!(!dart.is_convex());
+    }
+
+    #[test]
+    fn test_contains_square() {
+        let square = Polygon::from_vec(vec![0, 0, 4, 0, 4, 4, 0, 4]).unwrap();
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            assert!(square.contains(Point::new(2.0, 2.0), rule));
+            assert!(square.contains(Point::new(0.0, 0.0), rule));
+            assert!(!square.contains(Point::new(4.0, 2.0), rule));
+            assert!(!square.contains(Point::new(2.0, 4.0), rule));
+            assert!(!square.contains(Point::new(-1.0, 2.0), rule));
+        }
+    }
+
+    #[test]
+    fn test_contains_respects_fill_rule() {
+        let pentagram = Polygon::from_vec(vec![
+            0.0, -10.0, 5.88, 8.09, -9.51, -3.09, 9.51, -3.09, -5.88, 8.09,
+        ])
+        .unwrap();
+        let center = Point::new(0.0, 0.0);
+        assert_eq!(pentagram.winding_number(center).abs(), 2);
+        assert_eq!(pentagram.crossing_count(center), 2);
+        assert!(pentagram.contains(center, FillRule::NonZero));
+        assert!(!pentagram.contains(center, FillRule::EvenOdd));
+    }
+
+    #[test]
+    fn test_distance_to() {
+        let square = Polygon::from_vec(vec![0, 0, 4, 0, 4, 4, 0, 4]).unwrap();
+        assert_eq!(square.distance_to(Point::new(1.0, 2.0)), 1.0);
+        assert_eq!(square.distance_to(Point::new(7.0, 8.0)), 5.0);
+    }
+
+    #[test]
+    fn test_is_simple_and_repair() {
+        let square = Polygon::from_vec(vec![0, 0, 4, 0, 4, 4, 0, 4]).unwrap();
+        assert!(square.is_simple());
+        let bowtie = Polygon::from_vec(vec![0.0, 0.0, 4.0, 4.0, 4.0, 0.0, 0.0, 4.0]).unwrap();
+        assert!(!bowtie.is_simple());
+        let repaired = bowtie.repair(FillRule::NonZero);
+        assert_eq!(repaired.polygons.len(), 2);
+        assert!(repaired.rings().all(|ring| ring.is_simple()));
+        assert_relative_eq!(repaired.area(), 8.0, epsilon = 1e-9);
+        for p in [
+            Point::new(0.5, 2.0),
+            Point::new(2.0, 0.5),
+            Point::new(2.0, 3.5),
+        ] {
+            assert_eq!(
+                repaired.contains(p),
+                bowtie.contains(p, FillRule::NonZero),
+                "{:?}",
+                p
+            );
+        }
+    }
 }
@@ -0,0 +1,303 @@
+use super::multi_polygon::rings_contain;
+use super::{
+    Affine2, Circle, Ellipse, FillRule, MultiPolygon, Path, Point, Polygon, PolygonWithHoles, Rect,
+    RoundedRect, Transform,
+};
+use crate::error::{Error, Result};
+
+/// A shape with an exact description, rendered from its signed distance instead of being
+/// flattened into a polygon.
+pub trait Primitive {
+    /// Distance from `p` to the outline, negative inside.
+    fn signed_distance(&self, p: Point<f64>) -> f64;
+
+    fn bounding_box(&self) -> Rect<f64>;
+
+    fn area(&self) -> f64;
+
+    /// A counter-clockwise polygon (see `Orientation`) within `tolerance` of the outline.
+    fn flatten(&self, tolerance: f64) -> Polygon<f64>;
+
+    /// Fails on the first parameter that is NaN or infinite.
+    fn check_finite(&self) -> Result<()>;
+
+    fn contains(&self, p: Point<f64>) -> bool {
+        self.signed_distance(p) <= 0.0
+    }
+}
+
+pub(crate) fn check_params(params: &[(&'static str, f64)]) -> Result<()> {
+    match params.iter().find(|(_, value)| !value.is_finite()) {
+        Some(&(name, _)) => Err(Error::NonFiniteParameter { name }),
+        None => Ok(()),
+    }
+}
+
+/// Any shape a `Drawing` can hold.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Geometry {
+    Polygon(Polygon<f64>),
+    /// Polygons with holes, filled in one pass over all their rings.
+    MultiPolygon(MultiPolygon),
+    /// Lines and Bézier curves, flattened and filled like a multi-polygon.
+    Path(Path),
+    Circle(Circle),
+    Ellipse(Ellipse),
+    RoundedRect(RoundedRect),
+}
+
+impl From<Polygon<f64>> for Geometry {
+    fn from(polygon: Polygon<f64>) -> Self {
+        Geometry::Polygon(polygon)
+    }
+}
+
+impl From<MultiPolygon> for Geometry {
+    fn from(multi: MultiPolygon) -> Self {
+        Geometry::MultiPolygon(multi)
+    }
+}
+
+impl From<PolygonWithHoles> for Geometry {
+    fn from(polygon: PolygonWithHoles) -> Self {
+        Geometry::MultiPolygon(polygon.into())
+    }
+}
+
+impl From<Path> for Geometry {
+    fn from(path: Path) -> Self {
+        Geometry::Path(path)
+    }
+}
+
+impl From<Circle> for Geometry {
+    fn from(circle: Circle) -> Self {
+        Geometry::Circle(circle)
+    }
+}
+
+impl From<Ellipse> for Geometry {
+    fn from(ellipse: Ellipse) -> Self {
+        Geometry::Ellipse(ellipse)
+    }
+}
+
+impl From<RoundedRect> for Geometry {
+    fn from(rect: RoundedRect) -> Self {
+        Geometry::RoundedRect(rect)
+    }
+}
+
+impl Geometry {
+    /// Everything but polygons, multi-polygons and paths.
+    pub fn as_primitive(&self) -> Option<&dyn Primitive> {
+        match self {
+            Geometry::Polygon(_) | Geometry::MultiPolygon(_) | Geometry::Path(_) => None,
+            Geometry::Circle(circle) => Some(circle),
+            Geometry::Ellipse(ellipse) => Some(ellipse),
+            Geometry::RoundedRect(rect) => Some(rect),
+        }
+    }
+
+    pub fn as_polygon(&self) -> Option<&Polygon<f64>> {
+        match self {
+            Geometry::Polygon(polygon) => Some(polygon),
+            _ => None,
+        }
+    }
+
+    pub fn bounding_box(&self) -> Option<Rect<f64>> {
+        match self {
+            Geometry::Polygon(polygon) => polygon.bounding_box(),
+            Geometry::MultiPolygon(multi) => multi.bounding_box(),
+            Geometry::Path(path) => path.bounding_box(),
+            _ => self
+                .as_primitive()
+                .map(|primitive| primitive.bounding_box()),
+        }
+    }
+
+    pub fn area(&self) -> f64 {
+        match self {
+            Geometry::Polygon(polygon) => polygon.area(),
+            Geometry::MultiPolygon(multi) => multi.area(),
+            // Subpaths running the other way are holes
+            Geometry::Path(path) => path
+                .rings()
+                .iter()
+                .map(|ring| ring.signed_area())
+                .sum::<f64>()
+                .abs(),
+            _ => self
+                .as_primitive()
+                .map_or(0.0, |primitive| primitive.area()),
+        }
+    }
+
+    /// Whether `p` is inside. `rule` does not matter for primitives.
+    pub fn contains(&self, p: Point<f64>, rule: FillRule) -> bool {
+        match self {
+            Geometry::Polygon(polygon) => polygon.contains(p, rule),
+            Geometry::MultiPolygon(multi) => multi.contains_with(p, rule),
+            Geometry::Path(path) => rings_contain(path.rings().iter(), p, rule),
+            _ => self
+                .as_primitive()
+                .is_some_and(|primitive| primitive.contains(p)),
+        }
+    }
+
+    pub fn check_finite(&self) -> Result<()> {
+        match self {
+            Geometry::Polygon(polygon) => polygon.check_finite(),
+            Geometry::MultiPolygon(multi) => multi.check_finite(),
+            Geometry::Path(path) => path.check_finite(),
+            _ => self.as_primitive().map_or(Ok(()), |p| p.check_finite()),
+        }
+    }
+
+    /// The rings enclosing the shape, to be filled together: a primitive is flattened
+    /// within `tolerance` into a single ring, and a path into one ring per subpath.
+    pub fn to_rings(&self, tolerance: f64) -> Vec<Polygon<f64>> {
+        match self {
+            Geometry::Polygon(polygon) => vec![polygon.clone()],
+            Geometry::MultiPolygon(multi) => multi.rings().cloned().collect(),
+            Geometry::Path(path) => path.flatten(tolerance),
+            _ => self
+                .as_primitive()
+                .map(|primitive| primitive.flatten(tolerance))
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+// Largest flattening error when a rounded rectangle has to become a polygon
+const TRANSFORM_TOLERANCE: f64 = 0.05;
+
+// Circles stay circles under similarities and become ellipses otherwise. Rounded
+// rectangles stay rounded rectangles under similarities, and so do sharp ones whenever
+// their sides stay perpendicular. Otherwise the corners would turn into elliptical arcs,
+// so the rectangle is flattened.
+impl Transform for Geometry {
+    fn transform(&self, t: &Affine2) -> Self {
+        match self {
+            Geometry::Polygon(polygon) => Geometry::Polygon(polygon.transform(t)),
+            Geometry::MultiPolygon(multi) => Geometry::MultiPolygon(multi.transform(t)),
+            Geometry::Path(path) => Geometry::Path(path.transform(t)),
+            Geometry::Circle(circle) => {
+                let ellipse = Ellipse::from(*circle).transform(t);
+                if (ellipse.rx - ellipse.ry).abs() <= 1e-12 * ellipse.rx {
+                    Geometry::Circle(Circle::new(ellipse.center, ellipse.rx))
+                } else {
+                    Geometry::Ellipse(ellipse)
+                }
+            }
+            Geometry::Ellipse(ellipse) => Geometry::Ellipse(ellipse.transform(t)),
+            Geometry::RoundedRect(rect) => {
+                let (sin, cos) = rect.angle.sin_cos();
+                let u = Point::new(t.a * cos + t.b * sin, t.d * cos + t.e * sin);
+                let v = Point::new(t.b * cos - t.a * sin, t.e * cos - t.d * sin);
+                let (su, sv) = (u.x.hypot(u.y), v.x.hypot(v.y));
+                let sheared = (u.x * v.x + u.y * v.y).abs() > 1e-12 * su * sv;
+                let stretched = rect.radius > 0.0 && (su - sv).abs() > 1e-12 * su.max(sv);
+                if sheared || stretched {
+                    // The error grows with the scale, so flatten finer to stay within the
+                    // tolerance afterwards
+                    let tolerance = TRANSFORM_TOLERANCE / su.max(sv).max(1.0);
+                    return Geometry::Polygon(rect.flatten(tolerance).transform(t));
+                }
+                Geometry::RoundedRect(RoundedRect {
+                    center: t.apply(rect.center),
+                    half_width: rect.half_width * su,
+                    half_height: rect.half_height * sv,
+                    radius: rect.radius * su,
+                    angle: u.y.atan2(u.x),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod primitive_tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_geometry_dispatch() {
+        let square = Polygon::from_vec(vec![0.0, 0.0, 2.0, 0.0, 2.0, 2.0, 0.0, 2.0]).unwrap();
+        let circle = Circle::new(Point::new(1.0, 1.0), 1.0);
+        let shapes = [Geometry::from(square), Geometry::from(circle)];
+        assert_relative_eq!(shapes[0].area(), 4.0);
+        assert_relative_eq!(shapes[1].area(), std::f64::consts::PI);
+        for shape in &shapes {
+            assert!(shape.contains(Point::new(1.0, 1.5), FillRule::NonZero));
+            assert!(!shape.contains(Point::new(3.0, 1.0), FillRule::NonZero));
+            assert_eq!(
+                shape.bounding_box(),
+                Some(Rect::new(Point::new(0.0, 0.0), Point::new(2.0, 2.0)))
+            );
+        }
+        let hole = Polygon::from_vec(vec![0.5, 0.5, 0.5, 1.5, 1.5, 1.5, 1.5, 0.5]).unwrap();
+        let ring = PolygonWithHoles::new(shapes[0].as_polygon().unwrap().clone(), vec![hole]);
+        let ring = Geometry::from(ring);
+        assert_relative_eq!(ring.area(), 3.0);
+        assert!(!ring.contains(Point::new(1.0, 1.0), FillRule::NonZero));
+        assert!(ring.contains(Point::new(1.8, 1.0), FillRule::EvenOdd));
+        assert_eq!(ring.bounding_box(), shapes[0].bounding_box());
+        assert_eq!(ring.to_rings(0.1).len(), 2);
+
+        let bad = Geometry::from(Circle::new(Point::new(0.0, f64::NAN), 1.0));
+        assert!(matches!(
+            bad.check_finite(),
+            Err(Error::NonFiniteParameter { name: "center.y" })
+        ));
+    }
+
+    #[test]
+    fn test_transforms() {
+        let circle = Geometry::from(Circle::new(Point::new(1.0, 0.0), 2.0));
+        let t = Affine2::rotate(0.5).then(&Affine2::scale(3.0, 3.0));
+        match circle.transform(&t) {
+            Geometry::Circle(c) => assert_relative_eq!(c.radius, 6.0, epsilon = 1e-12),
+            other => panic!("{:?}", other),
+        }
+        assert!(matches!(
+            circle.transform(&Affine2::scale(2.0, 1.0)),
+            Geometry::Ellipse(_)
+        ));
+
+        let rect = Geometry::from(RoundedRect::new(Point::new(1.0, 1.0), 2.0, 1.0, 0.5, 0.0));
+        let t = Affine2::rotate(0.3).then(&Affine2::scale(2.0, 2.0));
+        match rect.transform(&t) {
+            Geometry::RoundedRect(r) => {
+                let center = t.apply(Point::new(1.0, 1.0));
+                assert_relative_eq!(r.center.x, center.x);
+                assert_relative_eq!(r.center.y, center.y);
+                assert_relative_eq!(r.half_width, 4.0);
+                assert_relative_eq!(r.half_height, 2.0);
+                assert_relative_eq!(r.radius, 1.0);
+                assert_relative_eq!(r.angle, 0.3);
+            }
+            other => panic!("{:?}", other),
+        }
+        // Stretching bends the rounded corners into elliptical arcs
+        let t = Affine2::scale(2.0, 3.0);
+        match rect.transform(&t) {
+            Geometry::Polygon(p) => assert_relative_eq!(p.area(), 6.0 * rect.area(), epsilon = 0.2),
+            other => panic!("{:?}", other),
+        }
+        let sharp = Geometry::from(RoundedRect::new(Point::new(1.0, 1.0), 2.0, 1.0, 0.0, 0.0));
+        match sharp.transform(&t) {
+            Geometry::RoundedRect(r) => {
+                assert_eq!(r.center, Point::new(2.0, 3.0));
+                assert_eq!((r.half_width, r.half_height, r.radius), (4.0, 3.0, 0.0));
+            }
+            other => panic!("{:?}", other),
+        }
+        assert!(matches!(
+            rect.transform(&Affine2::shear(0.5, 0.0)),
+            Geometry::Polygon(_)
+        ));
+    }
+}
@@ -0,0 +1,103 @@
+use num_traits::Num;
+
+use super::Point;
+
+// An axis-aligned rectangle. `min` and `max` are inclusive corners.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Rect<T> {
+    pub min: Point<T>,
+    pub max: Point<T>,
+}
+
+impl<T> Rect<T> {
+    pub fn new(min: Point<T>, max: Point<T>) -> Self {
+        Rect { min, max }
+    }
+}
+
+impl<T> Rect<T>
+where
+    T: Copy + PartialOrd,
+{
+    /// The smallest rectangle holding all points, or `None` if there are none.
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a Point<T>>) -> Option<Self>
+    where
+        T: 'a,
+    {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+        Some(points.fold(Rect::new(first, first), |rect, p| rect.include(*p)))
+    }
+
+    /// Grows the rectangle to hold `p`.
+    pub fn include(self, p: Point<T>) -> Self {
+        let pick = |a: T, b: T, take_b: bool| if take_b { b } else { a };
+        Rect {
+            min: Point::new(
+                pick(self.min.x, p.x, p.x < self.min.x),
+                pick(self.min.y, p.y, p.y < self.min.y),
+            ),
+            max: Point::new(
+                pick(self.max.x, p.x, p.x > self.max.x),
+                pick(self.max.y, p.y, p.y > self.max.y),
+            ),
+        }
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        self.include(other.min).include(other.max)
+    }
+
+    pub fn contains(&self, p: Point<T>) -> bool {
+        self.min.x <= p.x && p.x <= self.max.x && self.min.y <= p.y && p.y <= self.max.y
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+}
+
+impl<T> Rect<T>
+where
+    T: Copy + Num,
+{
+    pub fn width(&self) -> T {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> T {
+        self.max.y - self.min.y
+    }
+}
+
+#[cfg(test)]
+mod rect_tests {
+    use super::{Point, Rect};
+
+    #[test]
+    fn test_from_points() {
+        let points = [Point::new(3, 1), Point::new(-2, 4), Point::new(0, -5)];
+        let rect = Rect::from_points(&points).unwrap();
+        assert_eq!(rect, Rect::new(Point::new(-2, -5), Point::new(3, 4)));
+        assert_eq!((rect.width(), rect.height()), (5, 9));
+        assert!(Rect::<i32>::from_points(&[]).is_none());
+    }
+
+    #[test]
+    fn test_contains_and_intersects() {
+        let a = Rect::new(Point::new(0.0, 0.0), Point::new(2.0, 2.0));
+        let b = Rect::new(Point::new(2.0, 1.0), Point::new(3.0, 3.0));
+        let c = Rect::new(Point::new(2.5, 0.0), Point::new(3.0, 0.5));
+        assert!(a.contains(Point::new(2.0, 0.0)));
+        assert!(!a.contains(Point::new(2.1, 0.0)));
+        assert!(a.intersects(&b));
+        assert!(!a.intersects(&c));
+        assert_eq!(
+            a.union(c),
+            Rect::new(Point::new(0.0, 0.0), Point::new(3.0, 2.0))
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use super::ellipse::circle_steps;
+use super::primitive::{check_params, Primitive};
+use super::{Point, Polygon, Rect};
+use crate::error::Result;
+
+/// A rectangle with its corners rounded to `radius`, centered on `center` and rotated by
+/// `angle` radians from +x towards +y. The radius is capped at half the shorter side.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoundedRect {
+    pub center: Point<f64>,
+    pub half_width: f64,
+    pub half_height: f64,
+    pub radius: f64,
+    pub angle: f64,
+}
+
+impl RoundedRect {
+    pub fn new(
+        center: Point<f64>,
+        half_width: f64,
+        half_height: f64,
+        radius: f64,
+        angle: f64,
+    ) -> Self {
+        RoundedRect {
+            center,
+            half_width,
+            half_height,
+            radius,
+            angle,
+        }
+    }
+
+    /// An axis-aligned rounded rectangle filling `rect`.
+    pub fn from_rect(rect: &Rect<f64>, radius: f64) -> Self {
+        let half = (rect.max - rect.min) * 0.5;
+        Self::new(rect.min + half, half.x, half.y, radius, 0.0)
+    }
+
+    fn effective_radius(&self) -> f64 {
+        self.radius
+            .min(self.half_width)
+            .min(self.half_height)
+            .max(0.0)
+    }
+}
+
+impl Primitive for RoundedRect {
+    fn signed_distance(&self, p: Point<f64>) -> f64 {
+        if self.half_width <= 0.0 || self.half_height <= 0.0 {
+            return f64::INFINITY;
+        }
+        let r = self.effective_radius();
+        let (sin, cos) = self.angle.sin_cos();
+        let d = p - self.center;
+        // Distances past the straight parts of the sides, in the rectangle's own frame
+        let qx = (cos * d.x + sin * d.y).abs() - (self.half_width - r);
+        let qy = (cos * d.y - sin * d.x).abs() - (self.half_height - r);
+        qx.max(0.0).hypot(qy.max(0.0)) + qx.max(qy).min(0.0) - r
+    }
+
+    fn bounding_box(&self) -> Rect<f64> {
+        let (sin, cos) = self.angle.sin_cos();
+        let r = Point::new(
+            (self.half_width * cos).abs() + (self.half_height * sin).abs(),
+            (self.half_width * sin).abs() + (self.half_height * cos).abs(),
+        );
+        Rect::new(self.center - r, self.center + r)
+    }
+
+    fn area(&self) -> f64 {
+        let r = self.effective_radius();
+        4.0 * self.half_width.max(0.0) * self.half_height.max(0.0) - (4.0 - PI) * r * r
+    }
+
+    fn flatten(&self, tolerance: f64) -> Polygon<f64> {
+        let r = self.effective_radius();
+        let (hx, hy) = (self.half_width - r, self.half_height - r);
+        let steps = if r > 0.0 {
+            circle_steps(r, tolerance).div_ceil(4)
+        } else {
+            0
+        };
+        let (sin, cos) = self.angle.sin_cos();
+        let mut vertices = Vec::with_capacity(4 * (steps + 1));
+        // Counter-clockwise from the corner at +x, +y
+        for (k, (sx, sy)) in [(1.0, 1.0), (-1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)]
+            .into_iter()
+            .enumerate()
+        {
+            for i in 0..=steps {
+                let t = FRAC_PI_2 * (k as f64 + i as f64 / steps.max(1) as f64);
+                let (x, y) = (sx * hx + r * t.cos(), sy * hy + r * t.sin());
+                vertices.push(self.center + Point::new(cos * x - sin * y, sin * x + cos * y));
+            }
+        }
+        vertices.dedup();
+        Polygon { vertices }
+    }
+
+    fn check_finite(&self) -> Result<()> {
+        check_params(&[
+            ("center.x", self.center.x),
+            ("center.y", self.center.y),
+            ("half_width", self.half_width),
+            ("half_height", self.half_height),
+            ("radius", self.radius),
+            ("angle", self.angle),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod rounded_rect_tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_signed_distance() {
+        let rect = RoundedRect::new(Point::new(0.0, 0.0), 4.0, 2.0, 1.0, 0.0);
+        assert_relative_eq!(rect.signed_distance(Point::new(0.0, 0.0)), -2.0);
+        assert_relative_eq!(rect.signed_distance(Point::new(6.0, 0.0)), 2.0);
+        assert_relative_eq!(rect.signed_distance(Point::new(0.0, 3.0)), 1.0);
+        // The corner is cut by the rounding
+        assert!(!rect.contains(Point::new(3.9, 1.9)));
+        assert!(rect.contains(Point::new(3.5, 1.5)));
+        let rotated = RoundedRect {
+            angle: FRAC_PI_2,
+            ..rect
+        };
+        assert!(rotated.contains(Point::new(0.0, 3.5)));
+        assert!(!rotated.contains(Point::new(3.5, 0.0)));
+    }
+
+    #[test]
+    fn test_flatten_and_area() {
+        let rect = RoundedRect::new(Point::new(5.0, 5.0), 4.0, 3.0, 1.5, 0.3);
+        let flat = rect.flatten(0.01);
+        assert_relative_eq!(flat.signed_area(), rect.area(), max_relative = 0.01);
+        let bbox = rect.bounding_box();
+        assert!(flat.vertices.iter().all(|p| bbox.contains(*p)));
+        for p in &flat.vertices {
+            assert!(rect.signed_distance(*p).abs() < 1e-9);
+        }
+
+        let sharp =
+            RoundedRect::from_rect(&Rect::new(Point::new(0.0, 0.0), Point::new(4.0, 2.0)), 0.0);
+        assert_eq!(sharp.flatten(0.1).vertices.len(), 4);
+        assert_relative_eq!(sharp.area(), 8.0);
+    }
+}
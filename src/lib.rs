@@ -1,6 +1,13 @@
 pub mod algorithms;
 pub mod canvas;
+pub mod drawing;
 pub mod engine;
+pub mod error;
 pub mod geometry;
 pub mod nums;
 pub mod utils;
+
+#[cfg(test)]
+mod test_utils;
+
+pub use error::{Error, Result};
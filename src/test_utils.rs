@@ -0,0 +1,8 @@
+// Fixtures shared by the unit tests.
+
+use crate::geometry::Polygon;
+
+/// An axis-aligned square with its lowest corner at `(x, y)`.
+pub(crate) fn square(x: f64, y: f64, size: f64) -> Polygon<f64> {
+    Polygon::from_vec(vec![x, y, x + size, y, x + size, y + size, x, y + size]).unwrap()
+}
@@ -1 +1,3 @@
+pub mod rng;
 
+pub use rng::Rng;